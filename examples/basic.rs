@@ -32,7 +32,8 @@ fn main() {
                 let anns: Vec<String> = segment
                     .annotations
                     .iter()
-                    .map(|ann| {
+                    .map(|&id| {
+                        let ann = parsed.annotation(id);
                         let attrs = format_attrs(&ann.attrs);
                         if attrs.is_empty() {
                             ann.tag.clone()
@@ -48,11 +49,12 @@ fn main() {
         if !parsed.markers.is_empty() {
             println!("Markers:");
             for marker in &parsed.markers {
-                let attrs = format_attrs(&marker.annotation.attrs);
+                let ann = parsed.annotation(marker.annotation);
+                let attrs = format_attrs(&ann.attrs);
                 let tag = if attrs.is_empty() {
-                    marker.annotation.tag.clone()
+                    ann.tag.clone()
                 } else {
-                    format!("{} [{}]", marker.annotation.tag, attrs)
+                    format!("{} [{}]", ann.tag, attrs)
                 };
                 println!("- @{} {}", marker.pos, tag);
             }
@@ -82,15 +84,26 @@ fn build_config() -> ParserConfig {
     cfg
 }
 
+fn format_attr_value(value: &AttrValue) -> String {
+    match value {
+        AttrValue::Bool(b) => b.to_string(),
+        AttrValue::Str(s) => format!("\"{}\"", s),
+        AttrValue::Int(i) => i.to_string(),
+        AttrValue::Float(f) => f.to_string(),
+        AttrValue::List(items) => items
+            .iter()
+            .map(format_attr_value)
+            .collect::<Vec<_>>()
+            .join(","),
+    }
+}
+
 fn format_attrs(attrs: &std::collections::HashMap<String, AttrValue>) -> String {
     let mut pairs: Vec<_> = attrs.iter().collect();
     pairs.sort_by_key(|(k, _)| *k);
     pairs
         .into_iter()
-        .map(|(k, v)| match v {
-            AttrValue::Bool(b) => format!("{}={}", k, b),
-            AttrValue::Str(s) => format!("{}=\"{}\"", k, s),
-        })
+        .map(|(k, v)| format!("{}={}", k, format_attr_value(v)))
         .collect::<Vec<_>>()
         .join(", ")
 }
@@ -1,27 +1,118 @@
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Python bindings (`pyo3` pyclasses) over this crate's parser, built only
+/// when the `python` feature is enabled.
+#[cfg(feature = "python")]
+mod python_bindings;
+#[cfg(feature = "python")]
+pub use python_bindings::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub start_line: u32,
+    pub start_col: u32,
+    pub end_line: u32,
+    pub end_col: u32,
+}
+
+#[derive(Debug, Clone)]
 pub enum AttrValue {
     Bool(bool),
     Str(String),
+    Int(i64),
+    Float(f64),
+    /// Values of a repeated attribute (e.g. `id=1 id=2`), produced by
+    /// `DuplicateAttrPolicy::CommaList` when typed coercion applies to that
+    /// attribute; otherwise duplicates collapse into a comma-joined `Str`.
+    List(Vec<AttrValue>),
+}
+
+// Can't derive `Eq`: `f64` isn't `Eq`. Floats are compared by bit pattern,
+// so a NaN (coercion accepts literal text such as `NaN` or `inf`) equals
+// itself and `Eq` stays reflexive; this lets every other type in this
+// module keep deriving `Eq` through `Annotation`'s `HashMap<String,
+// AttrValue>`.
+impl PartialEq for AttrValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AttrValue::Bool(a), AttrValue::Bool(b)) => a == b,
+            (AttrValue::Str(a), AttrValue::Str(b)) => a == b,
+            (AttrValue::Int(a), AttrValue::Int(b)) => a == b,
+            (AttrValue::Float(a), AttrValue::Float(b)) => a.to_bits() == b.to_bits(),
+            (AttrValue::List(a), AttrValue::List(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for AttrValue {}
+
+/// Expected type for a specific tag's attribute in
+/// `ParserConfig::attr_value_schema`, forcing coercion independent of
+/// `coerce_attr_values`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttrValueType {
+    Str,
+    Int,
+    Float,
+    Bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Annotation {
     pub tag: String,
+    /// The `prefix` of a `prefix:local` tag name (e.g. `doc` in `<doc:cite>`),
+    /// or `None` for an unprefixed tag. `tag` always holds the local name.
+    pub prefix: Option<String>,
     pub attrs: HashMap<String, AttrValue>,
+    /// Input byte range of the opening tag token itself (e.g. `<cite id=1>`).
+    pub span: Option<Span>,
+    /// Input byte range of the closing tag token (e.g. `</cite>`), when the
+    /// tag was closed by a genuine end tag rather than recovery or autoclose.
+    pub end_span: Option<Span>,
+}
+
+/// A lightweight handle into a [`ParseResult`]'s `annotations` arena.
+/// Cheap to copy and compare; dereference it with [`ParseResult::annotation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnnotationId(usize);
+
+impl AnnotationId {
+    /// Constructs an id for an arena rebuilt outside `Parser::intern_annotation`
+    /// (e.g. `PyParseResult::from_dict`, which assigns ids in push order).
+    pub fn new(idx: usize) -> Self {
+        AnnotationId(idx)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Segment {
     pub text: String,
-    pub annotations: Vec<Annotation>,
+    pub annotations: Vec<AnnotationId>,
+    pub span: Option<Span>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Marker {
     pub pos: usize,
-    pub annotation: Annotation,
+    pub annotation: AnnotationId,
+    pub span: Option<Span>,
+}
+
+/// One deduplicated citation target produced by
+/// [`ParseResult::resolve_references`]: every span citing `key`, collapsed
+/// into a single entry with merged attributes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub key: String,
+    /// Output-text byte ranges (into `ParseResult::text`) of every
+    /// segment/marker that cites this key: segment ranges in text order,
+    /// then marker positions.
+    pub spans: Vec<Range<usize>>,
+    pub attrs: HashMap<String, AttrValue>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -46,9 +137,137 @@ pub enum StrayEndTagPolicy {
     Passthrough,
 }
 
+/// What to do when a tag repeats the same attribute name (e.g. `<cite id=1
+/// id=2>`), which LLMs emit fairly often when citing multiple things.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateAttrPolicy {
+    /// Keep only the last occurrence, silently dropping earlier ones.
+    LastWins,
+    /// Merge every occurrence. Produces a comma-joined `AttrValue::Str`
+    /// (e.g. `"1,2"`), or an `AttrValue::List` when typed coercion applies
+    /// to the attribute (so each element is coerced individually).
+    CommaList,
+}
+
+/// Canonical word-casing scheme `ParserConfig::tag_case_style` normalizes
+/// incoming tag names to before `tag_aliases`/`recognized_tags` matching,
+/// mirroring the casing transforms the `heck` crate exposes. A name is
+/// split into words on `_`, `-`, whitespace, and lower-to-upper case
+/// boundaries, then re-joined in the chosen style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagCaseStyle {
+    /// `bold_text`
+    Snake,
+    /// `bold-text`
+    Kebab,
+    /// `boldText`
+    Camel,
+    /// `BoldText`
+    Pascal,
+    /// `BOLD_TEXT`
+    ScreamingSnake,
+}
+
+/// How confident a [`Diagnostic`] is that something needs attention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+}
+
+/// The kind of recovery event a [`Diagnostic`] reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// A recognized tag was never explicitly closed.
+    UnclosedTag,
+    /// An end tag had no matching open tag.
+    StrayEndTag,
+    /// A tag outside `recognized_tags` was encountered.
+    UnknownTag,
+    /// An open tag was force-closed because another tag started.
+    AmbiguousAutoclose,
+}
+
+/// A recorded recovery action, so downstream consumers can tell an
+/// authoritative annotation from a guessed one (borrowed from rustc's
+/// "collect issues with spans rather than throwing" approach).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub severity: Severity,
+    pub tag: String,
+    pub strategy_applied: Option<RecoveryStrategy>,
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+/// The kind of malformed attribute text a [`AttrDiagnostic`] reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttrDiagnosticKind {
+    /// A quoted value's closing quote never appeared before the tag ended;
+    /// the rest of the attribute text was taken as its value.
+    UnterminatedQuote,
+    /// An `=` appeared with nothing usable following it.
+    MissingValue,
+    /// The same attribute name appeared more than once on the same tag.
+    DuplicateAttr,
+    /// An unquoted value contained a raw `<`, almost always a sign that a
+    /// missing closing quote swallowed the start of the next tag.
+    RawAngleInValue,
+}
+
+/// A recoverable problem found while parsing one tag's attribute text, from
+/// [`ParseResult::attr_diagnostics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrDiagnostic {
+    pub tag: String,
+    /// The attribute the problem was found on, when the parser had already
+    /// recognized a name at that point.
+    pub attr: Option<String>,
+    pub kind: AttrDiagnosticKind,
+}
+
+/// Which delimiter syntax the parser scans for inline annotations.
+///
+/// Recognized-tag filtering, case sensitivity, and recovery strategies are
+/// all applied by [`Parser`] on top of whichever profile is active; only the
+/// token-level scanning (where a tag starts/ends and how attrs are spelled)
+/// differs between profiles.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxProfile {
+    /// `<tag attr=val>...</tag>` / `<tag attr=val/>`, the original KindaXML syntax.
+    Angle,
+    /// `[[tag:value|text]]` wikilink-style markers, inspired by
+    /// pulldown-cmark-wikilink. `value` becomes the tag's `id` attribute;
+    /// omitting `|text` produces a zero-width [`Marker`] instead of a span.
+    /// Because each marker is a single self-contained token, recovery
+    /// strategies never come into play: there is no such thing as an
+    /// unclosed wikilink.
+    Wikilink,
+}
+
+/// How a `prefix:local` tag name (e.g. `<doc:cite>`) is checked against
+/// `recognized_tags`. `per_tag_recovery` always keys on the local name alone,
+/// regardless of this mode, so callers can namespace tags by source without
+/// duplicating recovery configuration per prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrefixMatchMode {
+    /// Ignore the prefix entirely; recognize by local name only.
+    LocalName,
+    /// Recognize by the full `prefix:local` string (so `recognized_tags`
+    /// must list the qualified names, e.g. `"doc:cite"`).
+    FullName,
+    /// Recognize by local name, but only if the prefix (when present) is
+    /// also listed in `recognized_prefixes`. An unlisted prefix falls
+    /// through to `unknown_mode` just like an unrecognized tag.
+    PrefixWhitelist,
+}
+
 #[derive(Debug, Clone)]
 pub struct ParserConfig {
     pub recognized_tags: HashSet<String>,
+    /// Prefixes allowed by `PrefixMatchMode::PrefixWhitelist`.
+    pub recognized_prefixes: HashSet<String>,
     pub per_tag_recovery: HashMap<String, RecoveryStrategy>,
     pub unknown_mode: UnknownMode,
     pub autoclose_on_any_tag: bool,
@@ -56,12 +275,49 @@ pub struct ParserConfig {
     pub trim_punctuation: bool,
     pub case_sensitive_tags: bool,
     pub stray_end_tag_policy: StrayEndTagPolicy,
+    pub track_positions: bool,
+    pub syntax: SyntaxProfile,
+    pub prefix_match_mode: PrefixMatchMode,
+    /// What to do when a tag repeats the same attribute name.
+    pub duplicate_attr_policy: DuplicateAttrPolicy,
+    /// If true, attribute values that parse cleanly as an integer or float
+    /// are coerced from `AttrValue::Str` to `Int`/`Float`. A value that
+    /// doesn't parse cleanly (or is overridden by `attr_value_schema`) is
+    /// left as `Str`.
+    pub coerce_attr_values: bool,
+    /// Per-`(tag, attr)` expected type, keyed by the tag's local name. Takes
+    /// priority over `coerce_attr_values` in both directions: it can force
+    /// coercion for one attribute of an otherwise-unconverted tag, or pin an
+    /// attribute to `Str` even when `coerce_attr_values` is on. Falls back
+    /// to `Str` if the value doesn't parse as the expected type.
+    pub attr_value_schema: HashMap<(String, String), AttrValueType>,
+    /// If true, delimiters and tag-name characters are folded through
+    /// `confusable_skeleton` before being matched, so fullwidth brackets
+    /// (`＜cite＞`) and homoglyph letters (Cyrillic `с` in place of `c`) are
+    /// recognized like their ASCII look-alikes. The matched text itself is
+    /// always preserved verbatim in the output `Segment`/`TagToken::raw`.
+    pub normalize_confusables: bool,
+    /// Canonical word-casing scheme incoming tag names are normalized to
+    /// (see [`TagCaseStyle`]) before `tag_aliases`/`recognized_tags`
+    /// matching. `None` leaves tag names untouched.
+    pub tag_case_style: Option<TagCaseStyle>,
+    /// Maps arbitrary source tag spellings onto a canonical recognized tag
+    /// name, applied after `tag_case_style` normalization. The resolved
+    /// name (not the original spelling) is what ends up in `Annotation.tag`.
+    pub tag_aliases: HashMap<String, String>,
+    /// If true, malformed attribute text (unterminated quotes, dangling
+    /// `=`, duplicate names, a raw `<` in an unquoted value) is recorded
+    /// into `ParseResult::attr_diagnostics` instead of being silently
+    /// recovered from. Off by default: collecting these costs nothing
+    /// callers don't opt into, same as `track_positions`.
+    pub collect_attr_diagnostics: bool,
 }
 
 impl Default for ParserConfig {
     fn default() -> Self {
         Self {
             recognized_tags: HashSet::new(),
+            recognized_prefixes: HashSet::new(),
             per_tag_recovery: HashMap::new(),
             unknown_mode: UnknownMode::Strip,
             autoclose_on_any_tag: true,
@@ -69,6 +325,56 @@ impl Default for ParserConfig {
             trim_punctuation: true,
             case_sensitive_tags: true,
             stray_end_tag_policy: StrayEndTagPolicy::Drop,
+            track_positions: false,
+            syntax: SyntaxProfile::Angle,
+            prefix_match_mode: PrefixMatchMode::LocalName,
+            duplicate_attr_policy: DuplicateAttrPolicy::LastWins,
+            coerce_attr_values: false,
+            attr_value_schema: HashMap::new(),
+            normalize_confusables: false,
+            tag_case_style: None,
+            tag_aliases: HashMap::new(),
+            collect_attr_diagnostics: false,
+        }
+    }
+}
+
+impl ParserConfig {
+    /// Default config tuned for LLM-style output: a handful of common tags
+    /// recognized case-insensitively, with `cite` recovering retroactively
+    /// to the start of its line and the rest recovering forward to the next
+    /// tag.
+    pub fn default_llm_friendly_config() -> ParserConfig {
+        let recognized_tags: HashSet<String> = ["cite", "note", "todo", "claim", "risk", "code"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut per_tag_recovery: HashMap<String, RecoveryStrategy> = HashMap::new();
+        per_tag_recovery.insert("cite".into(), RecoveryStrategy::RetroLine);
+        for tag in ["note", "todo", "claim", "risk", "code"] {
+            per_tag_recovery.insert(tag.into(), RecoveryStrategy::ForwardUntilTag);
+        }
+        ParserConfig {
+            recognized_tags,
+            per_tag_recovery,
+            trim_punctuation: true,
+            case_sensitive_tags: false,
+            ..ParserConfig::default()
+        }
+    }
+
+    /// Default config for parsing just `<cite>` tags, recovering
+    /// retroactively to the start of the line when unclosed.
+    pub fn default_cite_config() -> ParserConfig {
+        ParserConfig {
+            recognized_tags: ["cite"].iter().map(|s| s.to_string()).collect(),
+            per_tag_recovery: [("cite".into(), RecoveryStrategy::RetroLine)]
+                .iter()
+                .cloned()
+                .collect(),
+            trim_punctuation: true,
+            case_sensitive_tags: false,
+            ..ParserConfig::default()
         }
     }
 }
@@ -78,6 +384,316 @@ pub struct ParseResult {
     pub text: String,
     pub segments: Vec<Segment>,
     pub markers: Vec<Marker>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Recoverable attribute-text problems (unterminated quotes, dangling
+    /// `=`, duplicate names, a raw `<` in a value), one per occurrence.
+    /// `None` unless `ParserConfig::collect_attr_diagnostics` is set, so
+    /// existing callers that never opted in see no change at all.
+    pub attr_diagnostics: Option<Vec<AttrDiagnostic>>,
+    /// Arena backing every [`AnnotationId`] in `segments`/`markers`; segments
+    /// that overlap the same tag share one entry instead of each cloning it.
+    pub annotations: Vec<Annotation>,
+    /// Maps each contiguous run of `text` back to the byte range in the
+    /// original input it was copied from, in order. The output ranges tile
+    /// `text` with no gaps; stripped markup leaves a gap between one entry's
+    /// input range and the next's, rather than appearing as an entry of its
+    /// own. `trim_punctuation` only narrows annotation spans, so trimmed
+    /// text is still covered by an entry.
+    pub source_spans: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl ParseResult {
+    /// Looks up the annotation an [`AnnotationId`] refers to.
+    pub fn annotation(&self, id: AnnotationId) -> &Annotation {
+        &self.annotations[id.0]
+    }
+
+    /// Translates a byte offset in `text` back to the offset it came from in
+    /// the original input, via `source_spans`. An offset on the boundary
+    /// between two chunks (where stripped markup was) maps to the start of
+    /// the later chunk; an offset past the end of `text` resolves to the
+    /// input end of the last chunk.
+    pub fn output_to_input(&self, pos: usize) -> usize {
+        let idx = self
+            .source_spans
+            .partition_point(|(out, _)| out.start <= pos);
+        match idx.checked_sub(1) {
+            Some(i) => {
+                let (out, input) = &self.source_spans[i];
+                if pos <= out.end {
+                    input.start + (pos - out.start)
+                } else {
+                    input.end
+                }
+            }
+            None => 0,
+        }
+    }
+
+    /// Maps a byte offset in `text` to its 1-based `(line, column)`, column
+    /// counted in chars rather than bytes. Built by binary-searching the
+    /// newline positions in `text` itself, so — unlike `Segment`/`Marker`'s
+    /// `span` (populated only when `ParserConfig::track_positions` is on,
+    /// and pointing at the *original input*) — this always reflects where a
+    /// position landed in the reconstructed `text`, for any offset
+    /// (`Marker::pos`, a `Segment`'s start as accumulated while walking
+    /// `segments`, ...), regardless of how the `ParseResult` was produced.
+    pub fn line_col(&self, pos: usize) -> (u32, u32) {
+        let mut pos = pos.min(self.text.len());
+        while pos > 0 && !self.text.is_char_boundary(pos) {
+            pos -= 1;
+        }
+        let newline_offsets: Vec<usize> = self
+            .text
+            .bytes()
+            .enumerate()
+            .filter(|(_, b)| *b == b'\n')
+            .map(|(i, _)| i)
+            .collect();
+        let line_idx = newline_offsets.partition_point(|nl| *nl < pos);
+        let line_start = if line_idx == 0 {
+            0
+        } else {
+            newline_offsets[line_idx - 1] + 1
+        };
+        let col = self.text[line_start..pos].chars().count() as u32 + 1;
+        (line_idx as u32 + 1, col)
+    }
+
+    /// Groups every annotation tagged `tag` by its `key_attr` value (e.g.
+    /// `resolve_references("cite", "id")`), collapsing repeated citations of
+    /// the same key into one [`Reference`] with every citing span recorded
+    /// and attrs merged across occurrences (later occurrences win on
+    /// conflict). Annotations missing `key_attr` are skipped. References are
+    /// returned in the order their key is first seen, scanning every
+    /// segment in text order before any marker, so a key cited only by a
+    /// marker comes after every key cited by a segment.
+    pub fn resolve_references(&self, tag: &str, key_attr: &str) -> Vec<Reference> {
+        let mut order: Vec<String> = Vec::new();
+        let mut by_key: HashMap<String, Reference> = HashMap::new();
+        let mut record = |ann: &Annotation, span: Range<usize>| {
+            if ann.tag != tag {
+                return;
+            }
+            let Some(key_value) = ann.attrs.get(key_attr) else {
+                return;
+            };
+            let key = attr_value_as_raw_str(key_value);
+            let entry = by_key.entry(key.clone()).or_insert_with(|| {
+                order.push(key.clone());
+                Reference {
+                    key: key.clone(),
+                    spans: Vec::new(),
+                    attrs: HashMap::new(),
+                }
+            });
+            entry.spans.push(span);
+            for (k, v) in &ann.attrs {
+                entry.attrs.insert(k.clone(), v.clone());
+            }
+        };
+
+        let mut pos = 0usize;
+        for segment in &self.segments {
+            let span = pos..pos + segment.text.len();
+            pos += segment.text.len();
+            for id in &segment.annotations {
+                record(self.annotation(*id), span.clone());
+            }
+        }
+        for marker in &self.markers {
+            record(self.annotation(marker.annotation), marker.pos..marker.pos);
+        }
+
+        order
+            .into_iter()
+            .map(|key| by_key.remove(&key).expect("key was just inserted"))
+            .collect()
+    }
+
+    /// Reconstructs well-formed annotated markup from `segments`/`markers`,
+    /// the reverse of [`parse`]. Emits nested `<tag attr=...>text</tag>`
+    /// spans (or `[[tag:id|text]]` under [`SyntaxProfile::Wikilink`]),
+    /// self-closing tags for markers at their recorded `pos`, CDATA-wraps
+    /// text containing raw `<`/`>`, and quotes attribute values that need it.
+    /// Attributes are emitted in sorted-key order for determinism, since
+    /// `Annotation::attrs` is a `HashMap`.
+    pub fn to_markup(&self, cfg: &ParserConfig) -> String {
+        match cfg.syntax {
+            SyntaxProfile::Angle => self.to_angle_markup(),
+            SyntaxProfile::Wikilink => self.to_wikilink_markup(),
+        }
+    }
+
+    fn to_angle_markup(&self) -> String {
+        let mut out = String::new();
+        let mut stack: Vec<AnnotationId> = Vec::new();
+        let mut marker_idx = 0;
+        let mut pos = 0usize;
+
+        for segment in &self.segments {
+            let seg_start = pos;
+            let seg_end = pos + segment.text.len();
+
+            // `segment.annotations` is innermost-first (see
+            // `Parser::build_segments_for`); reverse it to get the
+            // outermost-first stack this segment's tags should open as.
+            let target: Vec<AnnotationId> = segment.annotations.iter().rev().copied().collect();
+            let common = stack
+                .iter()
+                .zip(target.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            for id in stack[common..].iter().rev() {
+                out.push_str(&self.render_close_tag(self.annotation(*id)));
+            }
+            for id in &target[common..] {
+                out.push_str(&self.render_open_tag(self.annotation(*id), false));
+            }
+            stack = target;
+
+            // Markers are zero-width and never split `spans`, so one they
+            // fall inside has to be spliced into the middle of this
+            // segment's text rather than only checked at its edges.
+            let mut cursor = seg_start;
+            while marker_idx < self.markers.len() && self.markers[marker_idx].pos <= seg_end {
+                let marker = &self.markers[marker_idx];
+                let mark_pos = marker.pos;
+                out.push_str(&render_segment_text(
+                    &segment.text[cursor - seg_start..mark_pos - seg_start],
+                ));
+                out.push_str(&self.render_open_tag(self.annotation(marker.annotation), true));
+                cursor = mark_pos;
+                marker_idx += 1;
+            }
+            out.push_str(&render_segment_text(&segment.text[cursor - seg_start..]));
+
+            pos = seg_end;
+        }
+
+        // Markers in a document with no other text (e.g. a bare `<todo/>`)
+        // never fall inside any segment's range above.
+        while marker_idx < self.markers.len() {
+            let marker = &self.markers[marker_idx];
+            out.push_str(&self.render_open_tag(self.annotation(marker.annotation), true));
+            marker_idx += 1;
+        }
+
+        for id in stack.iter().rev() {
+            out.push_str(&self.render_close_tag(self.annotation(*id)));
+        }
+
+        out
+    }
+
+    fn render_open_tag(&self, ann: &Annotation, self_closing: bool) -> String {
+        let mut out = format!("<{}", qualified_tag_name(&ann.prefix, &ann.tag));
+        let mut keys: Vec<&String> = ann.attrs.keys().collect();
+        keys.sort();
+        for key in keys {
+            out.push(' ');
+            out.push_str(key);
+            let value = &ann.attrs[key];
+            if !matches!(value, AttrValue::Bool(true)) {
+                out.push('=');
+                out.push_str(&escape_attr_value(&attr_value_as_raw_str(value)));
+            }
+        }
+        out.push_str(if self_closing { "/>" } else { ">" });
+        out
+    }
+
+    fn render_close_tag(&self, ann: &Annotation) -> String {
+        format!("</{}>", qualified_tag_name(&ann.prefix, &ann.tag))
+    }
+
+    fn to_wikilink_markup(&self) -> String {
+        let mut out = String::new();
+        let mut marker_idx = 0;
+        let mut pos = 0usize;
+
+        for segment in &self.segments {
+            let seg_start = pos;
+            let seg_end = pos + segment.text.len();
+
+            // An annotated wikilink segment is never split by a marker
+            // (markers only ever land inside unannotated runs, since a
+            // `[[tag:id|text]]` span is a single atomic token), so only the
+            // unannotated branch needs to splice markers into the text.
+            match segment.annotations.first() {
+                Some(id) => {
+                    let ann = self.annotation(*id);
+                    out.push_str(&format!(
+                        "[[{}{}|{}]]",
+                        ann.tag,
+                        wikilink_id_suffix(ann),
+                        segment.text
+                    ));
+                }
+                None => {
+                    let mut cursor = seg_start;
+                    while marker_idx < self.markers.len() && self.markers[marker_idx].pos <= seg_end
+                    {
+                        let marker = &self.markers[marker_idx];
+                        let mark_pos = marker.pos;
+                        out.push_str(&segment.text[cursor - seg_start..mark_pos - seg_start]);
+                        let ann = self.annotation(marker.annotation);
+                        out.push_str(&format!("[[{}{}]]", ann.tag, wikilink_id_suffix(ann)));
+                        cursor = mark_pos;
+                        marker_idx += 1;
+                    }
+                    out.push_str(&segment.text[cursor - seg_start..]);
+                }
+            }
+            pos = seg_end;
+        }
+
+        while marker_idx < self.markers.len() {
+            let marker = &self.markers[marker_idx];
+            let ann = self.annotation(marker.annotation);
+            out.push_str(&format!("[[{}{}]]", ann.tag, wikilink_id_suffix(ann)));
+            marker_idx += 1;
+        }
+
+        out
+    }
+}
+
+/// The `:id` suffix of a wikilink token, or empty when the annotation has no
+/// `id` attribute (a bare `[[tag]]`/`[[tag|text]]`).
+fn wikilink_id_suffix(ann: &Annotation) -> String {
+    match ann.attrs.get("id") {
+        Some(value) => format!(":{}", attr_value_as_raw_str(value)),
+        None => String::new(),
+    }
+}
+
+/// Wraps `text` in `<![CDATA[...]]>` if it contains a raw `<`/`>` that would
+/// otherwise be misread as markup when the output is reparsed.
+fn render_segment_text(text: &str) -> String {
+    if text.contains('<') || text.contains('>') {
+        format!("<![CDATA[{text}]]>")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Quotes `raw` for use as an attribute value if it contains whitespace or a
+/// character that would otherwise end the attribute/tag early.
+fn escape_attr_value(raw: &str) -> String {
+    let needs_quotes = raw.is_empty()
+        || raw
+            .chars()
+            .any(|ch| ch.is_whitespace() || matches!(ch, '"' | '\'' | '>' | '/' | '='));
+    if !needs_quotes {
+        return raw.to_string();
+    }
+    if raw.contains('"') && !raw.contains('\'') {
+        format!("'{raw}'")
+    } else {
+        format!("\"{}\"", raw.replace('"', "&quot;"))
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -90,38 +706,375 @@ enum TagKind {
 #[derive(Debug, Clone)]
 struct TagToken {
     raw: String,
+    /// Local name, as typed (original case); excludes any `prefix:`.
     name: String,
+    /// Prefix, as typed (original case), if the tag name was `prefix:local`.
+    prefix: Option<String>,
+    /// Normalized full `prefix:local` name, used for start/end structural
+    /// matching (so `<doc:cite>` only pairs with `</doc:cite>`).
     normalized_name: String,
+    /// Normalized local name, used for recognition and `per_tag_recovery`.
+    normalized_local: String,
     attrs: HashMap<String, AttrValue>,
+    /// Attribute-text problems found while parsing `attrs`, each paired
+    /// with the attribute name it was found on (when one was recognized).
+    /// Always empty for `TagKind::End` tags, which have no attributes.
+    attr_issues: Vec<AttrIssue>,
     kind: TagKind,
+    input_start: usize,
+    input_end: usize,
+}
+
+/// A single parsed `[[tag:value|text]]` token, the [`SyntaxProfile::Wikilink`]
+/// counterpart of [`TagToken`].
+#[derive(Debug, Clone)]
+struct WikilinkToken {
+    raw: String,
+    name: String,
+    normalized_name: String,
+    attrs: HashMap<String, AttrValue>,
+    text: Option<String>,
+    text_input_start: usize,
+    input_start: usize,
+    input_end: usize,
 }
 
 #[derive(Debug, Clone)]
 struct OpenTag {
     name: String,
+    prefix: Option<String>,
     normalized_name: String,
     attrs: HashMap<String, AttrValue>,
     start_pos: usize,
     line_start_at_open: usize,
     strategy: RecoveryStrategy,
+    input_start: usize,
+    input_end: usize,
+}
+
+/// A single item of the pull-based parsing stream produced by [`EventReader`].
+///
+/// `Text` borrows directly from the original input, so consuming an
+/// `EventReader` is zero-copy for everything except the owned `Annotation`
+/// payloads. Unlike [`ParseResult`], the event stream has no arena to index
+/// into, so `Marker` carries its `Annotation` inline rather than an
+/// [`AnnotationId`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<'a> {
+    Text(&'a str),
+    StartAnnotation(Annotation),
+    EndAnnotation { tag: String },
+    Marker {
+        pos: usize,
+        annotation: Annotation,
+        span: Option<Span>,
+    },
+    Recovered(RecoveryStrategy),
+}
+
+/// Lazily consumable view over a parse, in the style of xml-rs's `EventReader`.
+///
+/// It honors the same [`ParserConfig`] as [`parse`] (recognized tags, case
+/// sensitivity, unknown-tag handling, recovery strategies); `parse` is in
+/// fact implemented by folding this reader's events back into segments, so
+/// the two always agree.
+pub struct EventReader<'a> {
+    events: std::vec::IntoIter<Event<'a>>,
+}
+
+impl<'a> EventReader<'a> {
+    pub fn new(input: &'a str, config: &'a ParserConfig) -> Self {
+        let parser = Parser::scan(input, config);
+        let events = parser.build_events();
+        EventReader {
+            events: events.into_iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for EventReader<'a> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events.next()
+    }
 }
 
 pub fn parse(input: &str, config: &ParserConfig) -> ParseResult {
-    let mut parser = Parser::new(input, config);
-    parser.run();
-    parser.finish()
+    let parser = Parser::scan(input, config);
+    let events = parser.build_events();
+    parser.fold_events(events)
+}
+
+/// Resumable parser for LLM output that arrives in fragments, reporting
+/// progress as an [`Event`] stream.
+///
+/// This is a thin wrapper over [`IncrementalParser`] — the two used to be
+/// independent implementations of the same resumable feed/finish scan, which
+/// only invited them to drift; now there's a single scanner, and this type
+/// just re-expresses its finalized `Segment`/`Marker` output as `Event`s,
+/// diffing each segment's annotation-id stack into `StartAnnotation`/
+/// `EndAnnotation` pairs the same way [`ParseResult::to_markup`] re-derives
+/// open/close tags from that stack. `finish` applies the configured
+/// `RecoveryStrategy` to whatever is still open, exactly like the batch
+/// parser, so feeding the same bytes in arbitrary chunk boundaries and
+/// calling `finish` is byte-identical to concatenating them and calling
+/// [`parse`] once.
+pub struct StreamingParser {
+    inner: IncrementalParser,
+    stack: Vec<AnnotationId>,
+    out_pos: usize,
+    text_store: Vec<String>,
+}
+
+impl StreamingParser {
+    pub fn new(config: ParserConfig) -> Self {
+        Self {
+            inner: IncrementalParser::new(config),
+            stack: Vec::new(),
+            out_pos: 0,
+            text_store: Vec::new(),
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &str) -> Vec<Event<'_>> {
+        let output = self.inner.feed(chunk);
+
+        enum Action {
+            Close(AnnotationId),
+            Open(AnnotationId),
+            Text(usize),
+            Marker(AnnotationId, usize, Option<Span>),
+        }
+
+        let mut actions = Vec::new();
+        let mut marker_idx = 0;
+
+        for segment in &output.segments {
+            let seg_start = self.out_pos;
+            let seg_end = seg_start + segment.text.len();
+
+            // `segment.annotations` is innermost-first (see
+            // `Parser::build_segments_for`); reverse it to get the
+            // outermost-first stack this segment's tags should open as.
+            let target: Vec<AnnotationId> = segment.annotations.iter().rev().copied().collect();
+            let common = self
+                .stack
+                .iter()
+                .zip(target.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            for id in self.stack[common..].iter().rev() {
+                actions.push(Action::Close(*id));
+            }
+            for id in &target[common..] {
+                actions.push(Action::Open(*id));
+            }
+            self.stack = target;
+
+            while marker_idx < output.markers.len() && output.markers[marker_idx].pos <= seg_end {
+                let marker = &output.markers[marker_idx];
+                actions.push(Action::Marker(marker.annotation, marker.pos, marker.span));
+                marker_idx += 1;
+            }
+
+            if !segment.text.is_empty() {
+                self.text_store.push(segment.text.clone());
+                actions.push(Action::Text(self.text_store.len() - 1));
+            }
+
+            self.out_pos = seg_end;
+        }
+
+        while marker_idx < output.markers.len() {
+            let marker = &output.markers[marker_idx];
+            actions.push(Action::Marker(marker.annotation, marker.pos, marker.span));
+            marker_idx += 1;
+        }
+
+        actions
+            .into_iter()
+            .map(|action| match action {
+                Action::Close(id) => Event::EndAnnotation {
+                    tag: self.inner.annotation(id).tag.clone(),
+                },
+                Action::Open(id) => Event::StartAnnotation(self.inner.annotation(id).clone()),
+                Action::Text(idx) => Event::Text(&self.text_store[idx]),
+                Action::Marker(id, pos, span) => Event::Marker {
+                    pos,
+                    annotation: self.inner.annotation(id).clone(),
+                    span,
+                },
+            })
+            .collect()
+    }
+
+    pub fn finish(self) -> ParseResult {
+        self.inner.finish()
+    }
+}
+
+/// What a single [`IncrementalParser::feed`] call produced.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FeedOutput {
+    /// How many more bytes of the accumulated input are safely accounted
+    /// for after this call. This can include bytes held back by an earlier
+    /// call, so it may exceed the length of the chunk just fed; the
+    /// still-unaccounted tail is buffered and re-considered on the next call.
+    pub consumed: usize,
+    /// Segments that finalized during this call, in order.
+    pub segments: Vec<Segment>,
+    /// Markers that finalized during this call, in order.
+    pub markers: Vec<Marker>,
+}
+
+/// Resumable parser that hands back `Segment`/`Marker` values as soon as
+/// their enclosing tags close, instead of `Event`s like [`StreamingParser`].
+///
+/// Text from the start of the earliest still-open tag onward is held back
+/// until that tag closes explicitly or is forced closed by autoclose; text
+/// before it is handed back as soon as it is scanned, and markers as soon
+/// as their self-closing tag is seen. Text already reported is never
+/// revisited for tags that open later, so a tag that is still unclosed at
+/// `finish` and recovers with `RetroLine` can annotate text an earlier
+/// `feed` already reported without that annotation. `finish` applies the
+/// configured `RecoveryStrategy` to whatever is still open, exactly like
+/// [`parse`], and its result is authoritative.
+///
+/// `AnnotationId`s inside `FeedOutput.segments`/`FeedOutput.markers` are
+/// resolved against this parser's own arena via [`IncrementalParser::annotation`],
+/// not against any `ParseResult` (there isn't one until `finish`).
+pub struct IncrementalParser {
+    config: ParserConfig,
+    buffer: String,
+    confirmed: usize,
+    reported_len: usize,
+    emitted_markers: usize,
+    annotations: Vec<Annotation>,
+}
+
+impl IncrementalParser {
+    pub fn new(config: ParserConfig) -> Self {
+        Self {
+            config,
+            buffer: String::new(),
+            confirmed: 0,
+            reported_len: 0,
+            emitted_markers: 0,
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Looks up the annotation an [`AnnotationId`] refers to.
+    pub fn annotation(&self, id: AnnotationId) -> &Annotation {
+        &self.annotations[id.0]
+    }
+
+    pub fn feed(&mut self, chunk: &str) -> FeedOutput {
+        self.buffer.push_str(chunk);
+        let cut = confirmed_prefix_len(&self.buffer);
+        let consumed = cut - self.confirmed;
+        self.confirmed = cut;
+
+        let mut parser = Parser::new(&self.buffer[..cut], &self.config);
+        parser.run();
+
+        let finalized_len = parser
+            .open
+            .iter()
+            .map(|open| open.start_pos)
+            .min()
+            .unwrap_or(parser.text.len());
+        let spans: Vec<(usize, usize, AnnotationId)> = parser
+            .spans
+            .iter()
+            .map(|(start, end, id, _)| (*start, *end, *id))
+            .collect();
+        let segments = parser.build_segments_for(&parser.text[..finalized_len], &spans);
+
+        // `segments` is rebuilt from scratch over the whole finalized prefix
+        // on every call and only splits where the annotation set changes, so
+        // a run of previously-reported plain text can silently re-merge with
+        // freshly-finalized text sharing the same (possibly empty)
+        // annotation set. Trim/split by the byte length already reported,
+        // not by Vec index, or a merge like that would drop the new text.
+        let mut new_segments = Vec::new();
+        let mut pos = 0usize;
+        for segment in &segments {
+            let start = pos;
+            let end = pos + segment.text.len();
+            pos = end;
+            if end <= self.reported_len {
+                continue;
+            }
+            if start >= self.reported_len {
+                new_segments.push(segment.clone());
+            } else {
+                let split_at = self.reported_len - start;
+                new_segments.push(Segment {
+                    text: segment.text[split_at..].to_string(),
+                    annotations: segment.annotations.clone(),
+                    span: self
+                        .config
+                        .track_positions
+                        .then(|| parser.span_from_output_range(self.reported_len, end)),
+                });
+            }
+        }
+        self.reported_len = finalized_len;
+
+        let new_markers = parser.markers[self.emitted_markers.min(parser.markers.len())..].to_vec();
+        self.emitted_markers = parser.markers.len();
+
+        self.annotations = parser.annotations;
+
+        FeedOutput {
+            consumed,
+            segments: new_segments,
+            markers: new_markers,
+        }
+    }
+
+    pub fn finish(self) -> ParseResult {
+        let parser = Parser::scan(&self.buffer, &self.config);
+        let events = parser.build_events();
+        parser.fold_events(events)
+    }
+}
+
+/// The longest prefix of `buffer` that cannot be extended into a different
+/// tag/CDATA boundary by more input: holds back a trailing `<...` with no
+/// closing `>` yet, or a `<![CDATA[` with no `]]>` yet.
+fn confirmed_prefix_len(buffer: &str) -> usize {
+    if let Some(start) = buffer.rfind("<![CDATA[")
+        && !buffer[start..].contains("]]>")
+    {
+        return start;
+    }
+    if let Some(lt) = buffer.rfind('<')
+        && !buffer[lt..].contains('>')
+    {
+        return lt;
+    }
+    buffer.len()
 }
 
 struct Parser<'a> {
     input: &'a str,
     config: &'a ParserConfig,
     recognized: HashSet<String>,
+    recognized_prefixes: HashSet<String>,
     per_tag_recovery: HashMap<String, RecoveryStrategy>,
     text: String,
     markers: Vec<Marker>,
-    spans: Vec<(usize, usize, Annotation)>,
+    spans: Vec<(usize, usize, AnnotationId, Option<RecoveryStrategy>)>,
+    annotations: Vec<Annotation>,
     open: Vec<OpenTag>,
     line_start: usize,
+    newline_offsets: Vec<usize>,
+    output_map: Vec<(usize, usize, usize)>,
+    diagnostics: Vec<Diagnostic>,
+    attr_diagnostics: Vec<AttrDiagnostic>,
 }
 
 impl<'a> Parser<'a> {
@@ -136,6 +1089,16 @@ impl<'a> Parser<'a> {
                 .collect()
         };
 
+        let recognized_prefixes = if config.case_sensitive_tags {
+            config.recognized_prefixes.clone()
+        } else {
+            config
+                .recognized_prefixes
+                .iter()
+                .map(|p| p.to_ascii_lowercase())
+                .collect()
+        };
+
         let per_tag_recovery = if config.case_sensitive_tags {
             config.per_tag_recovery.clone()
         } else {
@@ -146,45 +1109,229 @@ impl<'a> Parser<'a> {
                 .collect()
         };
 
+        let newline_offsets = if config.track_positions {
+            input
+                .bytes()
+                .enumerate()
+                .filter(|(_, b)| *b == b'\n')
+                .map(|(i, _)| i)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         Self {
             input,
             config,
             recognized,
+            recognized_prefixes,
             per_tag_recovery,
             text: String::new(),
             markers: Vec::new(),
             spans: Vec::new(),
+            annotations: Vec::new(),
             open: Vec::new(),
             line_start: 0,
+            newline_offsets,
+            output_map: Vec::new(),
+            diagnostics: Vec::new(),
+            attr_diagnostics: Vec::new(),
         }
     }
 
-    fn finish(mut self) -> ParseResult {
-        let end_pos = self.text.len();
-        self.close_all_open(end_pos);
-        let segments = self.build_segments();
+    /// Runs the scan to completion, closing any still-open tags, so the
+    /// resulting `spans`/`markers`/`text` are final and ready to be read off
+    /// either as events ([`EventReader`]) or folded into a [`ParseResult`].
+    fn scan(input: &'a str, config: &'a ParserConfig) -> Self {
+        let mut parser = Parser::new(input, config);
+        parser.run();
+        let end_pos = parser.text.len();
+        parser.close_all_open(end_pos);
+        parser
+    }
 
-        ParseResult {
-            text: self.text,
-            segments,
-            markers: self.markers,
+    /// Replays the final `spans`/`markers`/`text` as a flat, ordered event
+    /// stream. `Text` events never straddle a `push_text` chunk boundary, so
+    /// they always slice cleanly out of the original input.
+    fn build_events(&self) -> Vec<Event<'a>> {
+        if self.text.is_empty() {
+            return Vec::new();
         }
-    }
 
-    fn run(&mut self) {
-        let mut idx = 0;
-        let bytes = self.input.as_bytes();
-        while idx < self.input.len() {
-            if self.input[idx..].starts_with("<![CDATA[") {
-                let cdata_start = idx + "<![CDATA[".len();
-                if let Some(end) = self.input[cdata_start..].find("]]>") {
-                    let literal_end = cdata_start + end;
-                    let literal = &self.input[cdata_start..literal_end];
-                    self.push_text(literal);
-                    idx = literal_end + 3;
+        let mut bounds: Vec<usize> = vec![0, self.text.len()];
+        for (s, e, _, _) in &self.spans {
+            bounds.push(*s);
+            bounds.push(*e);
+        }
+        for (out_start, _, _) in &self.output_map {
+            bounds.push(*out_start);
+        }
+        for m in &self.markers {
+            bounds.push(m.pos);
+        }
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let mut start_order: Vec<usize> = (0..self.spans.len()).collect();
+        start_order.sort_by_key(|&i| (self.spans[i].0, std::cmp::Reverse(self.spans[i].1)));
+        let mut end_order: Vec<usize> = (0..self.spans.len()).collect();
+        end_order.sort_by_key(|&i| (self.spans[i].1, std::cmp::Reverse(self.spans[i].0)));
+
+        let mut events = Vec::new();
+        let mut prev = 0usize;
+        for &bound in &bounds {
+            if bound > prev {
+                let input_start = self.map_output_pos(prev, false);
+                let input_end = self.map_output_pos(bound, true);
+                if input_end > input_start {
+                    events.push(Event::Text(&self.input[input_start..input_end]));
+                }
+            }
+
+            for &i in &end_order {
+                let (_, end, annotation, recovered) = &self.spans[i];
+                if *end == bound {
+                    events.push(Event::EndAnnotation {
+                        tag: self.annotations[annotation.0].tag.clone(),
+                    });
+                    if let Some(strategy) = recovered {
+                        events.push(Event::Recovered(strategy.clone()));
+                    }
+                }
+            }
+            for marker in &self.markers {
+                if marker.pos == bound {
+                    events.push(Event::Marker {
+                        pos: marker.pos,
+                        annotation: self.annotations[marker.annotation.0].clone(),
+                        span: marker.span,
+                    });
+                }
+            }
+            for &i in &start_order {
+                let (start, _, annotation, _) = &self.spans[i];
+                if *start == bound {
+                    events.push(Event::StartAnnotation(self.annotations[annotation.0].clone()));
+                }
+            }
+
+            prev = bound;
+        }
+
+        events
+    }
+
+    /// Reimplements `parse` on top of the event stream: folds `Text` back
+    /// into a contiguous buffer and reopens/closes annotations by tag name,
+    /// guaranteeing the eager result matches what `EventReader` would yield.
+    fn fold_events(&self, events: Vec<Event<'a>>) -> ParseResult {
+        let mut text = String::new();
+        let mut markers = Vec::new();
+        let mut annotations: Vec<Annotation> = Vec::new();
+        let mut open: Vec<(usize, AnnotationId)> = Vec::new();
+        let mut spans: Vec<(usize, usize, AnnotationId)> = Vec::new();
+
+        for event in events {
+            match event {
+                Event::Text(s) => text.push_str(s),
+                Event::StartAnnotation(annotation) => {
+                    let id = AnnotationId(annotations.len());
+                    annotations.push(annotation);
+                    open.push((text.len(), id));
+                }
+                Event::EndAnnotation { tag } => {
+                    if let Some(idx) = open.iter().rposition(|(_, id)| annotations[id.0].tag == tag)
+                    {
+                        let (start, id) = open.remove(idx);
+                        spans.push((start, text.len(), id));
+                    }
+                }
+                Event::Marker {
+                    pos,
+                    annotation,
+                    span,
+                } => {
+                    let id = AnnotationId(annotations.len());
+                    annotations.push(annotation);
+                    markers.push(Marker {
+                        pos,
+                        annotation: id,
+                        span,
+                    });
+                }
+                Event::Recovered(_) => {}
+            }
+        }
+
+        let segments = self.build_segments_for(&text, &spans);
+        let source_spans = self
+            .output_map
+            .iter()
+            .map(|(out_start, in_start, len)| (*out_start..*out_start + *len, *in_start..*in_start + *len))
+            .collect();
+        ParseResult {
+            text,
+            segments,
+            markers,
+            diagnostics: self.diagnostics.clone(),
+            attr_diagnostics: self
+                .config
+                .collect_attr_diagnostics
+                .then(|| self.attr_diagnostics.clone()),
+            annotations,
+            source_spans,
+        }
+    }
+
+    fn diag_span(&self, start: usize, end: usize) -> Option<Span> {
+        self.config
+            .track_positions
+            .then(|| self.span_from_input_range(start, end))
+    }
+
+    fn record_attr_diagnostics(&mut self, token: &TagToken) {
+        if !self.config.collect_attr_diagnostics || token.attr_issues.is_empty() {
+            return;
+        }
+        for (attr, kind) in &token.attr_issues {
+            self.attr_diagnostics.push(AttrDiagnostic {
+                tag: token.name.clone(),
+                attr: attr.clone(),
+                kind: kind.clone(),
+            });
+        }
+    }
+
+    fn run(&mut self) {
+        match self.config.syntax {
+            SyntaxProfile::Angle => self.run_angle(),
+            SyntaxProfile::Wikilink => self.run_wikilink(),
+        }
+    }
+
+    /// Scans `self.input` for the next `<` with a byte search rather than
+    /// decoding chars: `<`, `>`, and the CDATA brackets are all ASCII, so
+    /// every split point this loop produces lands on a UTF-8 boundary and
+    /// the copied `&str` slices stay valid without per-char iteration.
+    fn run_angle(&mut self) {
+        if self.config.normalize_confusables {
+            self.run_angle_confusable();
+            return;
+        }
+
+        let mut idx = 0;
+        let bytes = self.input.as_bytes();
+        while idx < self.input.len() {
+            if self.input[idx..].starts_with("<![CDATA[") {
+                let cdata_start = idx + "<![CDATA[".len();
+                if let Some(end) = self.input[cdata_start..].find("]]>") {
+                    let literal_end = cdata_start + end;
+                    let literal = &self.input[cdata_start..literal_end];
+                    self.push_text(literal, cdata_start);
+                    idx = literal_end + 3;
                 } else {
                     let literal = &self.input[cdata_start..];
-                    self.push_text(literal);
+                    self.push_text(literal, cdata_start);
                     idx = self.input.len();
                 }
                 continue;
@@ -192,59 +1339,253 @@ impl<'a> Parser<'a> {
 
             if bytes[idx] == b'<'
                 && let Some((token, consumed)) = self.parse_tag(idx) {
-                    if self.should_treat_as_text(&token) {
-                        self.push_text(&token.raw);
-                        idx += consumed;
-                        continue;
-                    }
-
-                    match token.kind {
-                        TagKind::Start => {
-                            if self.is_recognized(&token.normalized_name) {
-                                self.maybe_autoclose_on_start_like(&token.normalized_name);
-                            }
-                            self.handle_start(token);
-                        }
-                        TagKind::SelfClosing => {
-                            if self.is_recognized(&token.normalized_name) {
-                                self.maybe_autoclose_on_start_like(&token.normalized_name);
-                            }
-                            self.handle_self_closing(token);
-                        }
-                        TagKind::End => {
-                            self.handle_end(token);
-                        }
-                    }
+                    self.dispatch_tag_token(token);
                     idx += consumed;
                     continue;
                 }
 
-            if let Some(next_lt) = self.input[idx + 1..].find('<') {
+            if let Some(next_lt) = memchr(b'<', &self.input.as_bytes()[idx + 1..]) {
                 let slice = &self.input[idx..idx + 1 + next_lt];
-                self.push_text(slice);
+                self.push_text(slice, idx);
                 idx += 1 + next_lt;
             } else {
                 let slice = &self.input[idx..];
-                self.push_text(slice);
+                self.push_text(slice, idx);
+                idx = self.input.len();
+            }
+        }
+    }
+
+    /// Confusable-aware counterpart to `run_angle`, used when
+    /// `normalize_confusables` is on. `run_angle`'s fast path scans raw ASCII
+    /// bytes, which can never see a multi-byte fullwidth bracket or homoglyph
+    /// letter; this variant decodes one char at a time instead and folds each
+    /// through `confusable_skeleton` before deciding whether it opens a tag.
+    fn run_angle_confusable(&mut self) {
+        let mut idx = 0;
+        while idx < self.input.len() {
+            if self.input[idx..].starts_with("<![CDATA[") {
+                let cdata_start = idx + "<![CDATA[".len();
+                if let Some(end) = self.input[cdata_start..].find("]]>") {
+                    let literal_end = cdata_start + end;
+                    let literal = &self.input[cdata_start..literal_end];
+                    self.push_text(literal, cdata_start);
+                    idx = literal_end + 3;
+                } else {
+                    let literal = &self.input[cdata_start..];
+                    self.push_text(literal, cdata_start);
+                    idx = self.input.len();
+                }
+                continue;
+            }
+
+            let ch = self.input[idx..].chars().next().expect("idx on a char boundary");
+            if confusable_skeleton(ch) == '<'
+                && let Some((token, consumed)) = self.parse_tag_confusable(idx) {
+                    self.dispatch_tag_token(token);
+                    idx += consumed;
+                    continue;
+                }
+
+            let mut next_idx = idx + ch.len_utf8();
+            while next_idx < self.input.len() {
+                let next_ch = self.input[next_idx..].chars().next().expect("idx on a char boundary");
+                if confusable_skeleton(next_ch) == '<' {
+                    break;
+                }
+                next_idx += next_ch.len_utf8();
+            }
+            let slice = &self.input[idx..next_idx];
+            self.push_text(slice, idx);
+            idx = next_idx;
+        }
+    }
+
+    /// Shared tail of `run_angle`/`run_angle_confusable`: once a `TagToken`
+    /// has been produced (by whichever scanner matched it), dispatch is
+    /// identical regardless of how its delimiters were spelled.
+    fn dispatch_tag_token(&mut self, token: TagToken) {
+        if self.should_treat_as_text(&token) {
+            self.record_unknown_tag(&token);
+            let token_start = token.input_start;
+            self.push_text(&token.raw, token_start);
+            return;
+        }
+
+        self.record_attr_diagnostics(&token);
+
+        match token.kind {
+            TagKind::Start => {
+                if self.is_recognized(&token.prefix, &token.normalized_local) {
+                    self.maybe_autoclose_on_start_like(&token.normalized_name);
+                }
+                self.handle_start(token);
+            }
+            TagKind::SelfClosing => {
+                if self.is_recognized(&token.prefix, &token.normalized_local) {
+                    self.maybe_autoclose_on_start_like(&token.normalized_name);
+                }
+                self.handle_self_closing(token);
+            }
+            TagKind::End => {
+                self.handle_end(token);
+            }
+        }
+    }
+
+    fn run_wikilink(&mut self) {
+        let mut idx = 0;
+        while idx < self.input.len() {
+            if self.input[idx..].starts_with("[[")
+                && let Some((token, consumed)) = self.parse_wikilink(idx)
+            {
+                self.handle_wikilink(token);
+                idx += consumed;
+                continue;
+            }
+
+            if let Some(next) = self.input[idx + 1..].find("[[") {
+                let slice = &self.input[idx..idx + 1 + next];
+                self.push_text(slice, idx);
+                idx += 1 + next;
+            } else {
+                let slice = &self.input[idx..];
+                self.push_text(slice, idx);
                 idx = self.input.len();
             }
         }
     }
 
+    /// Parses a single `[[tag:value|text]]` token starting at `start`.
+    /// `value` is optional and becomes the `id` attribute; `|text` is
+    /// optional and, when absent, the token is a zero-width marker.
+    fn parse_wikilink(&self, start: usize) -> Option<(WikilinkToken, usize)> {
+        let inner_start = start + 2;
+        let close_rel = self.input[inner_start..].find("]]")?;
+        let inner_end = inner_start + close_rel;
+        let raw_end = inner_end + 2;
+        let inner = &self.input[inner_start..inner_end];
+        let raw = &self.input[start..raw_end];
+
+        let (left, text) = match inner.find('|') {
+            Some(bar) => (&inner[..bar], Some(inner[bar + 1..].to_string())),
+            None => (inner, None),
+        };
+        let text_input_start = match inner.find('|') {
+            Some(bar) => inner_start + bar + 1,
+            None => inner_end,
+        };
+
+        let (name, value) = match left.find(':') {
+            Some(colon) => (&left[..colon], Some(left[colon + 1..].to_string())),
+            None => (left, None),
+        };
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut attrs = HashMap::new();
+        if let Some(value) = value {
+            attrs.insert("id".to_string(), AttrValue::Str(value));
+        }
+
+        Some((
+            WikilinkToken {
+                name: name.to_string(),
+                normalized_name: self.normalize_tag(name),
+                attrs,
+                text,
+                text_input_start,
+                raw: raw.to_string(),
+                input_start: start,
+                input_end: raw_end,
+            },
+            raw_end - start,
+        ))
+    }
+
+    fn handle_wikilink(&mut self, token: WikilinkToken) {
+        if !self.is_recognized(&None, &token.normalized_name) {
+            self.record_unknown_wikilink(&token);
+            match self.config.unknown_mode {
+                UnknownMode::Strip => {
+                    if let Some(text) = &token.text {
+                        self.push_text(text, token.text_input_start);
+                    }
+                }
+                UnknownMode::Passthrough | UnknownMode::TreatAsText => {
+                    let token_start = token.input_start;
+                    self.push_text(&token.raw, token_start);
+                }
+            }
+            return;
+        }
+
+        let span = self
+            .config
+            .track_positions
+            .then(|| self.span_from_input_range(token.input_start, token.input_end));
+        let annotation = Annotation {
+            tag: token.name,
+            prefix: None,
+            attrs: token.attrs,
+            span,
+            end_span: None,
+        };
+
+        match token.text {
+            Some(text) => {
+                let start = self.text.len();
+                self.push_text(&text, token.text_input_start);
+                let end = self.text.len();
+                let annotation = self.intern_annotation(annotation);
+                self.spans.push((start, end, annotation, None));
+            }
+            None => {
+                let annotation = self.intern_annotation(annotation);
+                self.markers.push(Marker {
+                    pos: self.text.len(),
+                    annotation,
+                    span,
+                });
+            }
+        }
+    }
+
+    fn record_unknown_wikilink(&mut self, token: &WikilinkToken) {
+        let action = match self.config.unknown_mode {
+            UnknownMode::Strip => "stripped",
+            UnknownMode::Passthrough => "passed through as literal text",
+            UnknownMode::TreatAsText => "treated as plain text",
+        };
+        self.diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::UnknownTag,
+            severity: Severity::Info,
+            tag: token.name.clone(),
+            strategy_applied: None,
+            message: format!("unknown tag [[{}]] was {}", token.name, action),
+            span: self.diag_span(token.input_start, token.input_end),
+        });
+    }
+
     fn parse_tag(&self, start: usize) -> Option<(TagToken, usize)> {
         let remaining = &self.input[start..];
-        let mut in_quote: Option<char> = None;
+        // '\'', '"' and '>' are all ASCII, so they can never occur as part of
+        // a multi-byte UTF-8 sequence; scanning raw bytes instead of decoding
+        // each char is safe here and skips the UTF-8 decode on every byte.
+        let bytes = remaining.as_bytes();
+        let mut in_quote: Option<u8> = None;
         let mut end_offset: Option<usize> = None;
-        for (i, ch) in remaining.char_indices() {
-            match ch {
-                '\'' | '"' => {
-                    if in_quote == Some(ch) {
+        for (i, &b) in bytes.iter().enumerate() {
+            match b {
+                b'\'' | b'"' => {
+                    if in_quote == Some(b) {
                         in_quote = None;
                     } else if in_quote.is_none() {
-                        in_quote = Some(ch);
+                        in_quote = Some(b);
                     }
                 }
-                '>' => {
+                b'>' => {
                     end_offset = Some(i);
                     break;
                 }
@@ -280,13 +1621,114 @@ impl<'a> Parser<'a> {
         }
 
         let (name, rest) = parse_name_and_rest(trimmed)?;
-        let attrs = if matches!(kind, TagKind::Start) {
-            parse_attrs(rest)
+        let (raw_attrs, attr_issues) = if matches!(kind, TagKind::Start) {
+            parse_attrs_multi(rest)
+        } else {
+            (HashMap::new(), Vec::new())
+        };
+
+        let (prefix, local) = split_prefix(&name);
+        let prefix = prefix.map(|p| p.to_string());
+        let local = self.resolve_tag_name(local);
+        let normalized_local = self.normalize_tag(&local);
+        let normalized_name = self.normalize_tag(&qualified_tag_name(&prefix, &local));
+        let attrs = self.resolve_duplicate_attrs(&local, raw_attrs);
+        let attrs = self.coerce_attrs(&local, attrs);
+        let final_kind = if self_closing {
+            TagKind::SelfClosing
+        } else {
+            kind
+        };
+
+        Some((
+            TagToken {
+                raw: raw.to_string(),
+                name: local,
+                prefix,
+                normalized_name,
+                normalized_local,
+                attrs,
+                attr_issues,
+                kind: final_kind,
+                input_start: start,
+                input_end: start + raw.len(),
+            },
+            raw.len(),
+        ))
+    }
+
+    /// Char-based counterpart to `parse_tag`, used only when
+    /// `normalize_confusables` is on. Delimiters, quotes and the tag name are
+    /// matched through `confusable_skeleton` instead of raw ASCII bytes, so
+    /// `＜cite id=1＞` and `<сite id=1>` (Cyrillic `с`) both parse like
+    /// `<cite id=1>`; `raw`/`name` still capture the exact original text.
+    fn parse_tag_confusable(&self, start: usize) -> Option<(TagToken, usize)> {
+        let remaining = &self.input[start..];
+        let mut in_quote: Option<char> = None;
+        let mut end_offset: Option<usize> = None;
+        for (i, ch) in remaining.char_indices() {
+            match confusable_skeleton(ch) {
+                folded @ ('\'' | '"') => {
+                    if in_quote == Some(folded) {
+                        in_quote = None;
+                    } else if in_quote.is_none() {
+                        in_quote = Some(folded);
+                    }
+                }
+                '>' => {
+                    end_offset = Some(i + ch.len_utf8());
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        let end_offset = end_offset?;
+        let raw = &remaining[..end_offset];
+        if raw.chars().count() < 3 {
+            return None;
+        }
+
+        let open_len = raw.chars().next().unwrap().len_utf8();
+        let close_len = raw.chars().next_back().unwrap().len_utf8();
+        let inner = &raw[open_len..raw.len() - close_len];
+        let mut trimmed = inner.trim();
+
+        let kind = if trimmed.chars().next().map(confusable_skeleton) == Some('/') {
+            let slash_len = trimmed.chars().next().unwrap().len_utf8();
+            trimmed = trimmed[slash_len..].trim_start();
+            TagKind::End
+        } else {
+            TagKind::Start
+        };
+
+        let mut self_closing = false;
+        if matches!(kind, TagKind::Start) {
+            let without_trailing = trimmed.trim_end();
+            if let Some(last) = without_trailing.chars().next_back()
+                && confusable_skeleton(last) == '/'
+            {
+                self_closing = true;
+                trimmed = without_trailing[..without_trailing.len() - last.len_utf8()].trim_end();
+            } else {
+                trimmed = without_trailing;
+            }
+        }
+
+        let (name, rest) = parse_name_and_rest_confusable(trimmed)?;
+        let raw_attrs = if matches!(kind, TagKind::Start) {
+            parse_attrs_multi_confusable(rest)
         } else {
             HashMap::new()
         };
 
-        let normalized_name = self.normalize_tag(&name);
+        let (prefix, local) = split_prefix(&name);
+        let prefix = prefix.map(|p| p.to_string());
+        let local = self.resolve_tag_name(local);
+        let normalized_local = self.normalize_tag(&local);
+        let normalized_name = self.normalize_tag(&qualified_tag_name(&prefix, &local));
+        let attrs = self.resolve_duplicate_attrs(&local, raw_attrs);
+        let attrs = self.coerce_attrs(&local, attrs);
         let final_kind = if self_closing {
             TagKind::SelfClosing
         } else {
@@ -296,30 +1738,79 @@ impl<'a> Parser<'a> {
         Some((
             TagToken {
                 raw: raw.to_string(),
-                name,
+                name: local,
+                prefix,
                 normalized_name,
+                normalized_local,
                 attrs,
+                // `parse_attrs_multi_confusable` doesn't surface attribute
+                // diagnostics; confusable-aware tags are a narrow profile
+                // and not this request's concern.
+                attr_issues: Vec::new(),
                 kind: final_kind,
+                input_start: start,
+                input_end: start + raw.len(),
             },
             raw.len(),
         ))
     }
 
+    /// Allocates `annotation` into the arena and returns a cheap handle to
+    /// it, so spans/markers that overlap the same tag can share one entry
+    /// instead of each cloning its `attrs` map.
+    fn intern_annotation(&mut self, annotation: Annotation) -> AnnotationId {
+        let id = AnnotationId(self.annotations.len());
+        self.annotations.push(annotation);
+        id
+    }
+
     fn normalize_tag(&self, name: &str) -> String {
-        if self.config.case_sensitive_tags {
+        let folded: String = if self.config.normalize_confusables {
+            name.chars().map(confusable_skeleton).collect()
+        } else {
             name.to_string()
+        };
+        if self.config.case_sensitive_tags {
+            folded
         } else {
-            name.to_ascii_lowercase()
+            folded.to_ascii_lowercase()
         }
     }
 
+    /// Resolves a tag's local name to the canonical spelling used for
+    /// `recognized_tags` membership and `Annotation.tag` — unlike
+    /// `normalize_tag`, this *replaces* the text callers see, rather than
+    /// just affecting a comparison. Order: confusable fold (if
+    /// `normalize_confusables`) → `tag_case_style` normalization →
+    /// `tag_aliases` lookup.
+    fn resolve_tag_name(&self, local: &str) -> String {
+        let folded: String = if self.config.normalize_confusables {
+            local.chars().map(confusable_skeleton).collect()
+        } else {
+            local.to_string()
+        };
+
+        let styled = match self.config.tag_case_style {
+            Some(style) => apply_tag_case_style(&folded, style),
+            None => folded,
+        };
+
+        self.config
+            .tag_aliases
+            .get(&styled)
+            .cloned()
+            .unwrap_or(styled)
+    }
+
     fn handle_start(&mut self, token: TagToken) {
-        let recognized = self.is_recognized(&token.normalized_name);
+        let recognized = self.is_recognized(&token.prefix, &token.normalized_local);
         if !recognized {
+            self.record_unknown_tag(&token);
             match self.config.unknown_mode {
                 UnknownMode::Strip => {}
                 UnknownMode::Passthrough | UnknownMode::TreatAsText => {
-                    self.push_text(&token.raw);
+                    let token_start = token.input_start;
+                    self.push_text(&token.raw, token_start);
                 }
             }
             return;
@@ -327,51 +1818,67 @@ impl<'a> Parser<'a> {
 
         let strategy = self
             .per_tag_recovery
-            .get(&token.normalized_name)
+            .get(&token.normalized_local)
             .cloned()
             .unwrap_or(RecoveryStrategy::RetroLine);
 
         let open = OpenTag {
             name: token.name,
+            prefix: token.prefix,
             normalized_name: token.normalized_name,
             attrs: token.attrs,
             start_pos: self.text.len(),
             line_start_at_open: self.line_start,
             strategy,
+            input_start: token.input_start,
+            input_end: token.input_end,
         };
         self.open.push(open);
     }
 
     fn handle_self_closing(&mut self, token: TagToken) {
-        let recognized = self.is_recognized(&token.normalized_name);
+        let recognized = self.is_recognized(&token.prefix, &token.normalized_local);
         if !recognized {
+            self.record_unknown_tag(&token);
             match self.config.unknown_mode {
                 UnknownMode::Strip => {}
                 UnknownMode::Passthrough | UnknownMode::TreatAsText => {
-                    self.push_text(&token.raw);
+                    let token_start = token.input_start;
+                    self.push_text(&token.raw, token_start);
                 }
             }
             return;
         }
 
+        let span = self
+            .config
+            .track_positions
+            .then(|| self.span_from_input_range(token.input_start, token.input_end));
         let annotation = Annotation {
             tag: token.name,
+            prefix: token.prefix,
             attrs: token.attrs,
+            span,
+            end_span: None,
         };
+        let annotation = self.intern_annotation(annotation);
         let marker = Marker {
             pos: self.text.len(),
             annotation,
+            span,
         };
         self.markers.push(marker);
     }
 
     fn handle_end(&mut self, token: TagToken) {
-        let recognized = self.is_recognized(&token.normalized_name);
+        let recognized = self.is_recognized(&token.prefix, &token.normalized_local);
         if !recognized {
+            self.record_unknown_tag(&token);
             match self.config.unknown_mode {
                 UnknownMode::Strip => {}
                 UnknownMode::Passthrough | UnknownMode::TreatAsText => {
-                    self.push_text(&token.raw);
+                    let token_start = token.input_start;
+                    self.push_text(&token.raw, token_start);
                 }
             }
             return;
@@ -390,16 +1897,55 @@ impl<'a> Parser<'a> {
             }
 
             if let Some(open) = self.open.pop() {
-                self.close_explicit(open, close_pos);
+                self.close_explicit(open, close_pos, Some((token.input_start, token.input_end)));
             }
         } else {
+            let action = match self.config.stray_end_tag_policy {
+                StrayEndTagPolicy::Drop => "dropped",
+                StrayEndTagPolicy::Passthrough => "kept as literal text",
+            };
+            self.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::StrayEndTag,
+                severity: Severity::Warning,
+                tag: token.name.clone(),
+                strategy_applied: None,
+                message: format!(
+                    "stray </{}> had no matching open tag, {}",
+                    qualified_tag_name(&token.prefix, &token.name),
+                    action
+                ),
+                span: self.diag_span(token.input_start, token.input_end),
+            });
             match self.config.stray_end_tag_policy {
                 StrayEndTagPolicy::Drop => {}
-                StrayEndTagPolicy::Passthrough => self.push_text(&token.raw),
+                StrayEndTagPolicy::Passthrough => {
+                    let token_start = token.input_start;
+                    self.push_text(&token.raw, token_start)
+                }
             }
         }
     }
 
+    fn record_unknown_tag(&mut self, token: &TagToken) {
+        let action = match self.config.unknown_mode {
+            UnknownMode::Strip => "stripped",
+            UnknownMode::Passthrough => "passed through as literal text",
+            UnknownMode::TreatAsText => "treated as plain text",
+        };
+        self.diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::UnknownTag,
+            severity: Severity::Info,
+            tag: token.name.clone(),
+            strategy_applied: None,
+            message: format!(
+                "unknown tag <{}> was {}",
+                qualified_tag_name(&token.prefix, &token.name),
+                action
+            ),
+            span: self.diag_span(token.input_start, token.input_end),
+        });
+    }
+
     fn close_all_open(&mut self, close_pos: usize) {
         while let Some(open) = self.open.pop() {
             self.close_tag(open, close_pos);
@@ -417,25 +1963,50 @@ impl<'a> Parser<'a> {
                 self.close_tag(t, close_pos);
             }
             if let Some(open) = self.open.pop() {
-                self.close_explicit(open, close_pos);
+                // Reopened rather than explicitly closed, so there is no end
+                // tag token to attach an `end_span` to.
+                self.close_explicit(open, close_pos, None);
             }
         }
     }
 
-    fn close_explicit(&mut self, open: OpenTag, close_pos: usize) {
+    fn close_explicit(&mut self, open: OpenTag, close_pos: usize, end_token: Option<(usize, usize)>) {
         if open.start_pos >= close_pos {
             return;
         }
+        let span = self
+            .config
+            .track_positions
+            .then(|| self.span_from_input_range(open.input_start, open.input_end));
+        let end_span = end_token
+            .filter(|_| self.config.track_positions)
+            .map(|(start, end)| self.span_from_input_range(start, end));
         let annotation = Annotation {
             tag: open.name,
+            prefix: open.prefix,
             attrs: open.attrs,
+            span,
+            end_span,
         };
-        self.spans.push((open.start_pos, close_pos, annotation));
+        let annotation = self.intern_annotation(annotation);
+        self.spans
+            .push((open.start_pos, close_pos, annotation, None));
     }
 
     fn close_tag(&mut self, open: OpenTag, close_pos: usize) {
+        let diag_span = self.diag_span(open.input_start, open.input_end);
+        let qname = qualified_tag_name(&open.prefix, &open.name);
         match open.strategy {
-            RecoveryStrategy::Noop => (),
+            RecoveryStrategy::Noop => {
+                self.diagnostics.push(Diagnostic {
+                    kind: DiagnosticKind::UnclosedTag,
+                    severity: Severity::Info,
+                    tag: open.name.clone(),
+                    strategy_applied: Some(RecoveryStrategy::Noop),
+                    message: format!("<{qname}> was never closed and Noop ignored it"),
+                    span: diag_span,
+                });
+            }
             RecoveryStrategy::RetroLine => {
                 let mut start = open.line_start_at_open;
                 let end = open.start_pos;
@@ -444,11 +2015,42 @@ impl<'a> Parser<'a> {
                 }
                 let (start, end) = self.trim_span(start, end);
                 if start < end {
+                    self.diagnostics.push(Diagnostic {
+                        kind: DiagnosticKind::UnclosedTag,
+                        severity: Severity::Warning,
+                        tag: open.name.clone(),
+                        strategy_applied: Some(RecoveryStrategy::RetroLine),
+                        message: format!(
+                            "<{qname}> was never closed; applied `RetroLine` to the {} chars before it",
+                            self.text[start..end].chars().count()
+                        ),
+                        span: diag_span,
+                    });
+                    let span = self
+                        .config
+                        .track_positions
+                        .then(|| self.span_from_input_range(open.input_start, open.input_end));
                     let annotation = Annotation {
                         tag: open.name,
+                        prefix: open.prefix,
                         attrs: open.attrs,
+                        span,
+                        end_span: None,
                     };
-                    self.spans.push((start, end, annotation));
+                    let annotation = self.intern_annotation(annotation);
+                    self.spans
+                        .push((start, end, annotation, Some(RecoveryStrategy::RetroLine)));
+                } else {
+                    self.diagnostics.push(Diagnostic {
+                        kind: DiagnosticKind::UnclosedTag,
+                        severity: Severity::Warning,
+                        tag: open.name.clone(),
+                        strategy_applied: Some(RecoveryStrategy::RetroLine),
+                        message: format!(
+                            "<{qname}> was never closed; `RetroLine` found no usable text before it"
+                        ),
+                        span: diag_span,
+                    });
                 }
             }
             RecoveryStrategy::ForwardUntilTag => {
@@ -467,10 +2069,24 @@ impl<'a> Parser<'a> {
             }
             RecoveryStrategy::ForwardNextToken => {
                 let slice = &self.text[open.start_pos..close_pos];
-                if let Some((token_start, token_end)) = next_token_bounds(slice) {
-                    let start = open.start_pos + token_start;
-                    let end = open.start_pos + token_end;
-                    self.push_forward_span(&open, start, end);
+                match next_token_bounds(slice) {
+                    Some((token_start, token_end)) => {
+                        let start = open.start_pos + token_start;
+                        let end = open.start_pos + token_end;
+                        self.push_forward_span(&open, start, end);
+                    }
+                    None => {
+                        self.diagnostics.push(Diagnostic {
+                            kind: DiagnosticKind::UnclosedTag,
+                            severity: Severity::Warning,
+                            tag: open.name.clone(),
+                            strategy_applied: Some(RecoveryStrategy::ForwardNextToken),
+                            message: format!(
+                                "<{qname}> was never closed; `ForwardNextToken` found no token after it"
+                            ),
+                            span: diag_span,
+                        });
+                    }
                 }
             }
         }
@@ -482,21 +2098,40 @@ impl<'a> Parser<'a> {
         }
         let (start, end) = self.trim_span(start, end);
         if start < end {
+            let span = self
+                .config
+                .track_positions
+                .then(|| self.span_from_input_range(open.input_start, open.input_end));
             let annotation = Annotation {
                 tag: open.name.clone(),
+                prefix: open.prefix.clone(),
                 attrs: open.attrs.clone(),
+                span,
+                end_span: None,
             };
-            self.spans.push((start, end, annotation));
+            let annotation = self.intern_annotation(annotation);
+            self.spans
+                .push((start, end, annotation, Some(open.strategy.clone())));
         }
     }
 
-    fn build_segments(&self) -> Vec<Segment> {
-        if self.text.is_empty() {
+    /// Builds `Segment`s for a `(text, spans)` pair, either produced by
+    /// folding events or taken from `IncrementalParser::feed`'s finalized
+    /// prefix. `text` is expected to be byte-identical to `self.text` or a
+    /// prefix of it (`build_events`/`fold_events` guarantee the former), so
+    /// `self`'s position helpers remain valid for computing each segment's
+    /// `Span`.
+    fn build_segments_for(
+        &self,
+        text: &str,
+        spans: &[(usize, usize, AnnotationId)],
+    ) -> Vec<Segment> {
+        if text.is_empty() {
             return Vec::new();
         }
 
-        let mut bounds: Vec<usize> = vec![0, self.text.len()];
-        for (s, e, _) in &self.spans {
+        let mut bounds: Vec<usize> = vec![0, text.len()];
+        for (s, e, _) in spans {
             bounds.push(*s);
             bounds.push(*e);
         }
@@ -510,14 +2145,21 @@ impl<'a> Parser<'a> {
             if start == end {
                 continue;
             }
-            let text = self.text[start..end].to_string();
-            let annotations = self
-                .spans
+            let seg_text = text[start..end].to_string();
+            let annotations = spans
                 .iter()
                 .filter(|(s, e, _)| *s <= start && *e >= end && *s != *e)
-                .map(|(_, _, ann)| ann.clone())
+                .map(|(_, _, id)| *id)
                 .collect();
-            segments.push(Segment { text, annotations });
+            let span = self
+                .config
+                .track_positions
+                .then(|| self.span_from_output_range(start, end));
+            segments.push(Segment {
+                text: seg_text,
+                annotations,
+                span,
+            });
         }
 
         segments
@@ -552,53 +2194,609 @@ impl<'a> Parser<'a> {
         (start, end)
     }
 
-    fn push_text(&mut self, text: &str) {
-        for (i, ch) in text.char_indices() {
-            if ch == '\n' {
-                self.line_start = self.text.len() + i + ch.len_utf8();
+    fn push_text(&mut self, text: &str, input_start: usize) {
+        if !text.is_empty() {
+            self.output_map
+                .push((self.text.len(), input_start, text.len()));
+        }
+        // '\n' is ASCII and can't appear inside a multi-byte UTF-8 sequence,
+        // so scanning bytes instead of decoding chars is safe here.
+        let base = self.text.len();
+        for (i, &b) in text.as_bytes().iter().enumerate() {
+            if b == b'\n' {
+                self.line_start = base + i + 1;
             }
         }
         self.text.push_str(text);
     }
 
-    fn is_recognized(&self, name: &str) -> bool {
-        self.recognized.contains(name)
+    /// Maps a byte offset in the reconstructed `text` back to the offset it
+    /// came from in the original input, using the chunk boundaries recorded
+    /// by `push_text`. Output chunks tile the text with no gaps, so a
+    /// boundary shared by two chunks is ambiguous: `is_end` picks the chunk
+    /// ending at that boundary rather than the one starting there, so a
+    /// segment's exclusive end lands just after its last real byte instead
+    /// of at the start of whatever (possibly stripped) markup follows it.
+    fn map_output_pos(&self, out_pos: usize, is_end: bool) -> usize {
+        let chunk_idx = if is_end {
+            self.output_map
+                .partition_point(|(out_start, _, _)| *out_start < out_pos)
+        } else {
+            self.output_map
+                .partition_point(|(out_start, _, _)| *out_start <= out_pos)
+        };
+        match chunk_idx.checked_sub(1).map(|i| self.output_map[i]) {
+            Some((out_start, in_start, len)) if out_pos <= out_start + len => {
+                in_start + (out_pos - out_start)
+            }
+            _ => 0,
+        }
     }
 
-    fn should_treat_as_text(&self, token: &TagToken) -> bool {
-        matches!(self.config.unknown_mode, UnknownMode::TreatAsText)
-            && !self.is_recognized(&token.normalized_name)
+    fn line_col_at(&self, pos: usize) -> (u32, u32) {
+        let line_idx = self.newline_offsets.partition_point(|nl| *nl < pos);
+        let line_start = if line_idx == 0 {
+            0
+        } else {
+            self.newline_offsets[line_idx - 1] + 1
+        };
+        let col = self.input[line_start..pos].chars().count() as u32 + 1;
+        (line_idx as u32 + 1, col)
     }
 
-    fn maybe_autoclose_on_start_like(&mut self, normalized_name: &str) {
-        if self.config.autoclose_on_same_tag
-            && self
-                .open
-                .iter()
-                .any(|o| o.normalized_name == normalized_name)
+    fn span_from_input_range(&self, start: usize, end: usize) -> Span {
+        let (start_line, start_col) = self.line_col_at(start);
+        let (end_line, end_col) = self.line_col_at(end);
+        Span {
+            start,
+            end,
+            start_line,
+            start_col,
+            end_line,
+            end_col,
+        }
+    }
+
+    fn span_from_output_range(&self, start: usize, end: usize) -> Span {
+        self.span_from_input_range(
+            self.map_output_pos(start, false),
+            self.map_output_pos(end, true),
+        )
+    }
+
+    /// Applies `attr_value_schema`/`coerce_attr_values` to every `Str`
+    /// attribute of `tag`. `Bool` flags are left alone; a schema entry takes
+    /// priority over the global flag, and any value that fails to parse as
+    /// its target type is left as `Str`. A `List` (from
+    /// `DuplicateAttrPolicy::CommaList`) is coerced element-wise.
+    fn coerce_attrs(&self, tag: &str, attrs: HashMap<String, AttrValue>) -> HashMap<String, AttrValue> {
+        if !self.config.coerce_attr_values && self.config.attr_value_schema.is_empty() {
+            return attrs;
+        }
+        attrs
+            .into_iter()
+            .map(|(name, value)| {
+                let expected = self
+                    .config
+                    .attr_value_schema
+                    .get(&(tag.to_string(), name.clone()))
+                    .copied();
+                let value = self.coerce_one_attr(value, expected);
+                (name, value)
+            })
+            .collect()
+    }
+
+    fn coerce_one_attr(&self, value: AttrValue, expected: Option<AttrValueType>) -> AttrValue {
+        if let AttrValue::List(items) = value {
+            return AttrValue::List(
+                items
+                    .into_iter()
+                    .map(|item| self.coerce_one_attr(item, expected))
+                    .collect(),
+            );
+        }
+        match expected {
+            Some(expected) => coerce_attr_value(value, expected),
+            None if self.config.coerce_attr_values => coerce_attr_value_auto(value),
+            None => value,
+        }
+    }
+
+    /// Applies `duplicate_attr_policy` to the raw, possibly-multi-valued
+    /// attrs `parse_attrs_multi` collected. `LastWins` keeps the final
+    /// occurrence, matching the old plain-`HashMap::insert` behavior; a
+    /// single occurrence is always passed through unchanged either way.
+    fn resolve_duplicate_attrs(
+        &self,
+        tag: &str,
+        raw: HashMap<String, Vec<AttrValue>>,
+    ) -> HashMap<String, AttrValue> {
+        raw.into_iter()
+            .map(|(name, mut values)| {
+                let value = if values.len() <= 1 {
+                    values.pop().unwrap_or(AttrValue::Bool(true))
+                } else {
+                    match self.config.duplicate_attr_policy {
+                        DuplicateAttrPolicy::LastWins => values.pop().unwrap(),
+                        DuplicateAttrPolicy::CommaList => {
+                            let typed = self.config.coerce_attr_values
+                                || self
+                                    .config
+                                    .attr_value_schema
+                                    .contains_key(&(tag.to_string(), name.clone()));
+                            if typed {
+                                AttrValue::List(values)
+                            } else {
+                                AttrValue::Str(
+                                    values
+                                        .iter()
+                                        .map(attr_value_as_raw_str)
+                                        .collect::<Vec<_>>()
+                                        .join(","),
+                                )
+                            }
+                        }
+                    }
+                };
+                (name, value)
+            })
+            .collect()
+    }
+
+    /// Checks `local` (and, depending on `PrefixMatchMode`, `prefix`) against
+    /// `recognized_tags`/`recognized_prefixes`. `local` must already be
+    /// normalized for case sensitivity; `prefix` is raw and normalized here
+    /// the same way, before the `FullName` branch qualifies `local` with it
+    /// or the `PrefixWhitelist` branch looks it up in `recognized_prefixes`.
+    fn is_recognized(&self, prefix: &Option<String>, local: &str) -> bool {
+        match self.config.prefix_match_mode {
+            PrefixMatchMode::LocalName => self.recognized.contains(local),
+            PrefixMatchMode::FullName => {
+                let normalized_prefix = prefix.as_ref().map(|p| self.normalize_tag(p));
+                let full = qualified_tag_name(&normalized_prefix, local);
+                self.recognized.contains(&full)
+            }
+            PrefixMatchMode::PrefixWhitelist => {
+                let prefix_ok = match prefix {
+                    Some(p) => self.recognized_prefixes.contains(&self.normalize_tag(p)),
+                    None => true,
+                };
+                prefix_ok && self.recognized.contains(local)
+            }
+        }
+    }
+
+    fn should_treat_as_text(&self, token: &TagToken) -> bool {
+        matches!(self.config.unknown_mode, UnknownMode::TreatAsText)
+            && !self.is_recognized(&token.prefix, &token.normalized_local)
+    }
+
+    fn maybe_autoclose_on_start_like(&mut self, normalized_name: &str) {
+        if self.config.autoclose_on_same_tag
+            && let Some(idx) = self
+                .open
+                .iter()
+                .rposition(|o| o.normalized_name == normalized_name)
         {
+            self.record_ambiguous_autoclose(idx);
             self.close_same_tag(normalized_name, self.text.len());
         }
-        if self.config.autoclose_on_any_tag {
+        if self.config.autoclose_on_any_tag && !self.open.is_empty() {
+            self.record_ambiguous_autoclose(0);
             self.close_all_open(self.text.len());
         }
     }
+
+    /// Records an [`Diagnostic`] for every still-open tag from `from_idx` onward,
+    /// right before it gets force-closed by `autoclose_on_same_tag`/`autoclose_on_any_tag`.
+    fn record_ambiguous_autoclose(&mut self, from_idx: usize) {
+        for open in &self.open[from_idx..] {
+            self.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::AmbiguousAutoclose,
+                severity: Severity::Info,
+                tag: open.name.clone(),
+                strategy_applied: Some(open.strategy.clone()),
+                message: format!(
+                    "<{}> was force-closed because another tag started before it closed",
+                    qualified_tag_name(&open.prefix, &open.name)
+                ),
+                span: self.diag_span(open.input_start, open.input_end),
+            });
+        }
+    }
+}
+
+/// Splits a captured tag name on its first `:`, xml-rs-namespace-style.
+/// A leading or trailing colon (empty prefix or local part) is not treated
+/// as a prefix split.
+fn split_prefix(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((prefix, local)) if !prefix.is_empty() && !local.is_empty() => (Some(prefix), local),
+        _ => (None, name),
+    }
+}
+
+/// Reassembles a `prefix:local` display name for diagnostic messages.
+fn qualified_tag_name(prefix: &Option<String>, local: &str) -> String {
+    match prefix {
+        Some(p) => format!("{p}:{local}"),
+        None => local.to_string(),
+    }
+}
+
+/// Finds the first occurrence of `byte` in `haystack`. `byte` is always one
+/// of the ASCII tag delimiters (`<`, `>`, etc.), which can never appear
+/// inside a multi-byte UTF-8 sequence, so a raw byte scan is always safe to
+/// use in place of a `str` search even over non-ASCII text.
+fn memchr(byte: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|&b| b == byte)
 }
 
 fn parse_name_and_rest(input: &str) -> Option<(String, &str)> {
-    let mut chars = input.char_indices().peekable();
-    if let Some((_, ch)) = chars.peek().copied() {
-        if !is_name_start(ch) {
-            return None;
+    let bytes = input.as_bytes();
+    if !bytes.first().is_some_and(|&b| is_name_start(b)) {
+        return None;
+    }
+
+    let mut end_idx = 0;
+    for &b in bytes {
+        if is_name_continue(b) {
+            end_idx += 1;
+        } else {
+            break;
         }
-    } else {
+    }
+
+    let name = input[..end_idx].to_string();
+    let rest = &input[end_idx..];
+    Some((name, rest))
+}
+
+/// States `parse_attrs_multi` steps through while scanning one tag's
+/// attribute text. Each variant carries whatever the next state needs to
+/// finish the attribute it's in the middle of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AttrScanState {
+    /// Between attributes, skipping whitespace.
+    Start,
+    /// Accumulated `name` so far, no value decided yet.
+    AfterName(String),
+    /// Saw `name=`; deciding whether a quote, an unquoted value, or nothing
+    /// usable comes next.
+    BeforeValue(String),
+    /// Inside a `'`/`"`-quoted value, waiting for the matching quote byte.
+    QuotedValue(String, u8),
+    Done,
+}
+
+/// One attribute-text issue found mid-scan, paired with the attribute name
+/// it was found on (when a name had already been recognized at that point).
+type AttrIssue = (Option<String>, AttrDiagnosticKind);
+
+/// Parses `input` — a tag's attribute text, already bounded by
+/// `Parser::parse_tag` finding the tag's terminating `>` — into its
+/// `name`/`name=value`/`name="quoted value"` pairs, collecting every
+/// occurrence of a repeated name in order rather than overwriting, so
+/// callers can apply `DuplicateAttrPolicy` themselves.
+///
+/// Steps through `input` one `AttrScanState` at a time instead of the
+/// straight-line scan this replaced, so malformed attribute text is
+/// recovered from *and* reported rather than silently swallowed: an
+/// unterminated quote, a dangling `=`, a repeated name, and a raw `<` inside
+/// an unquoted value are all still parsed leniently (the returned map is
+/// unchanged from before) but each now also appends an `AttrIssue` to the
+/// second return value, which `Parser::record_attr_diagnostics` turns into
+/// `ParseResult::attr_diagnostics` entries. There's no `NeedMoreInput`/
+/// `Invalid` state here: by the time `parse_tag` calls this, the tag's `>`
+/// has already been found, so `input` is always complete — a streaming
+/// caller that wanted to reuse this byte-at-a-time stepping on a truncated
+/// buffer would need to add one.
+fn parse_attrs_multi(mut input: &str) -> (HashMap<String, Vec<AttrValue>>, Vec<AttrIssue>) {
+    let mut attrs: HashMap<String, Vec<AttrValue>> = HashMap::new();
+    let mut issues: Vec<AttrIssue> = Vec::new();
+    let mut state = AttrScanState::Start;
+
+    let record = |attrs: &mut HashMap<String, Vec<AttrValue>>,
+                       issues: &mut Vec<AttrIssue>,
+                       name: &str,
+                       value: AttrValue| {
+        if attrs.contains_key(name) {
+            issues.push((Some(name.to_string()), AttrDiagnosticKind::DuplicateAttr));
+        }
+        attrs.entry(name.to_string()).or_default().push(value);
+    };
+
+    loop {
+        state = match state {
+            AttrScanState::Start => {
+                let trimmed = input.trim_start();
+                input = trimmed;
+                if input.is_empty() {
+                    AttrScanState::Done
+                } else {
+                    let mut idx = 0;
+                    for &b in input.as_bytes() {
+                        if is_name_continue(b) {
+                            idx += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                    if idx == 0 {
+                        // Not a valid name start (e.g. a stray byte with no
+                        // attribute before it); give up on the rest, same as
+                        // before this was rewritten.
+                        AttrScanState::Done
+                    } else {
+                        let name = input[..idx].to_string();
+                        input = &input[idx..];
+                        AttrScanState::AfterName(name)
+                    }
+                }
+            }
+            AttrScanState::AfterName(name) => {
+                input = input.trim_start();
+                if let Some(rest) = input.strip_prefix('=') {
+                    input = rest.trim_start();
+                    AttrScanState::BeforeValue(name)
+                } else {
+                    record(&mut attrs, &mut issues, &name, AttrValue::Bool(true));
+                    AttrScanState::Start
+                }
+            }
+            AttrScanState::BeforeValue(name) => match input.as_bytes().first() {
+                None => {
+                    issues.push((Some(name.clone()), AttrDiagnosticKind::MissingValue));
+                    record(&mut attrs, &mut issues, &name, AttrValue::Bool(true));
+                    AttrScanState::Done
+                }
+                Some(&quote @ (b'"' | b'\'')) => {
+                    input = &input[1..];
+                    AttrScanState::QuotedValue(name, quote)
+                }
+                Some(_) => {
+                    let mut end = 0;
+                    let mut saw_raw_angle = false;
+                    for (i, ch) in input.char_indices() {
+                        if ch.is_whitespace() || ch == '/' || ch == '>' {
+                            break;
+                        }
+                        if ch == '<' {
+                            saw_raw_angle = true;
+                        }
+                        end = i + ch.len_utf8();
+                    }
+                    if end == 0 && !input.is_empty() {
+                        end = input.len();
+                    }
+                    let val = &input[..end];
+                    if saw_raw_angle {
+                        issues.push((Some(name.clone()), AttrDiagnosticKind::RawAngleInValue));
+                    }
+                    record(
+                        &mut attrs,
+                        &mut issues,
+                        &name,
+                        AttrValue::Str(val.to_string()),
+                    );
+                    input = &input[end..];
+                    AttrScanState::Start
+                }
+            },
+            AttrScanState::QuotedValue(name, quote) => match memchr(quote, input.as_bytes()) {
+                Some(pos) => {
+                    let val = &input[..pos];
+                    record(
+                        &mut attrs,
+                        &mut issues,
+                        &name,
+                        AttrValue::Str(val.to_string()),
+                    );
+                    input = &input[pos + 1..];
+                    AttrScanState::Start
+                }
+                None => {
+                    // Unterminated quote: run until end of tag text, same
+                    // lenient recovery as before, now with a diagnostic.
+                    issues.push((Some(name.clone()), AttrDiagnosticKind::UnterminatedQuote));
+                    record(
+                        &mut attrs,
+                        &mut issues,
+                        &name,
+                        AttrValue::Str(input.to_string()),
+                    );
+                    input = "";
+                    AttrScanState::Done
+                }
+            },
+            AttrScanState::Done => break,
+        };
+    }
+
+    (attrs, issues)
+}
+
+/// Stringifies an `AttrValue` as raw attribute text: for
+/// `DuplicateAttrPolicy::CommaList`'s untyped comma-join (only `Str`/`Bool`
+/// reach it there, since coercion runs after duplicates are resolved), and
+/// for reference keys and re-emitted markup, where coerced `Int`/`Float`
+/// values and `List`s (joined with `,`) also occur.
+fn attr_value_as_raw_str(value: &AttrValue) -> String {
+    match value {
+        AttrValue::Str(s) => s.clone(),
+        AttrValue::Bool(b) => b.to_string(),
+        AttrValue::Int(i) => i.to_string(),
+        AttrValue::Float(f) => f.to_string(),
+        AttrValue::List(items) => items
+            .iter()
+            .map(attr_value_as_raw_str)
+            .collect::<Vec<_>>()
+            .join(","),
+    }
+}
+
+fn next_token_bounds(slice: &str) -> Option<(usize, usize)> {
+    let bytes = slice.as_bytes();
+    let mut start = None;
+    let mut end = None;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let b = bytes[idx];
+        // ASCII alphanumerics are the common case and can be checked without
+        // decoding a char; only fall back to full Unicode classification
+        // when a multi-byte sequence is actually present.
+        let (is_alnum, width) = if b.is_ascii() {
+            (b.is_ascii_alphanumeric(), 1)
+        } else {
+            let ch = slice[idx..].chars().next().expect("valid utf-8 boundary");
+            (ch.is_alphanumeric(), ch.len_utf8())
+        };
+        if is_alnum {
+            if start.is_none() {
+                start = Some(idx);
+            }
+            end = Some(idx + width);
+        } else if start.is_some() {
+            break;
+        }
+        idx += width;
+    }
+    match (start, end) {
+        (Some(s), Some(e)) => Some((s, e)),
+        _ => None,
+    }
+}
+
+fn is_name_start(b: u8) -> bool {
+    b.is_ascii_alphabetic()
+}
+
+fn is_name_continue(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b':' | b'.')
+}
+
+/// Folds a confusable character to the ASCII character it's commonly
+/// mistaken for, for `normalize_confusables` matching purposes. Covers the
+/// fullwidth ASCII block (`＜`, `＞`, fullwidth letters, ...) and a handful of
+/// Cyrillic/Greek letters that are visually identical to Latin ones. Not
+/// exhaustive — just the confusables LLMs actually tend to emit in markup.
+fn confusable_skeleton(ch: char) -> char {
+    match ch {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(ch as u32 - 0xFEE0).unwrap_or(ch),
+        '‹' => '<',
+        '›' => '>',
+        'а' | 'А' => 'a',
+        'е' | 'Е' => 'e',
+        'о' | 'О' => 'o',
+        'р' | 'Р' => 'p',
+        'с' | 'С' => 'c',
+        'х' | 'Х' => 'x',
+        'у' | 'У' => 'y',
+        'і' | 'І' => 'i',
+        'Α' => 'a',
+        'Β' => 'b',
+        'Ε' => 'e',
+        'Ο' => 'o',
+        'Τ' => 't',
+        _ => ch,
+    }
+}
+
+/// Splits a tag name into words on `_`, `-`, whitespace, and lower-to-upper
+/// case boundaries (so `BoldText`, `bold-text` and `bold_text` all split
+/// into `["Bold"/"bold", "Text"/"text"]`). Doesn't special-case acronym runs:
+/// an upper-to-lower boundary is not a split point, so `HTMLParser` stays
+/// the single word `["HTMLParser"]`.
+fn split_tag_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_is_lower_or_digit = false;
+    for ch in name.chars() {
+        if ch == '_' || ch == '-' || ch.is_whitespace() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_is_lower_or_digit = false;
+            continue;
+        }
+        if ch.is_uppercase() && prev_is_lower_or_digit && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_is_lower_or_digit = ch.is_lowercase() || ch.is_ascii_digit();
+        current.push(ch);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Uppercases a word's first char and lowercases the rest (`"TEXT"` and
+/// `"text"` both become `"Text"`).
+fn capitalize_word(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => {
+            first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase()
+        }
+        None => String::new(),
+    }
+}
+
+/// Normalizes a tag name to the given [`TagCaseStyle`] by splitting it into
+/// words (via `split_tag_words`) and re-joining them in that style.
+fn apply_tag_case_style(name: &str, style: TagCaseStyle) -> String {
+    let words = split_tag_words(name);
+    if words.is_empty() {
+        return name.to_string();
+    }
+    match style {
+        TagCaseStyle::Snake => words
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+        TagCaseStyle::Kebab => words
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("-"),
+        TagCaseStyle::ScreamingSnake => words
+            .iter()
+            .map(|w| w.to_uppercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+        TagCaseStyle::Camel => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| if i == 0 { w.to_lowercase() } else { capitalize_word(w) })
+            .collect::<Vec<_>>()
+            .join(""),
+        TagCaseStyle::Pascal => words.iter().map(|w| capitalize_word(w)).collect::<Vec<_>>().join(""),
+    }
+}
+
+fn is_name_start_confusable(ch: char) -> bool {
+    confusable_skeleton(ch).is_ascii_alphabetic()
+}
+
+fn is_name_continue_confusable(ch: char) -> bool {
+    let folded = confusable_skeleton(ch);
+    folded.is_ascii_alphanumeric() || matches!(folded, '_' | '-' | ':' | '.')
+}
+
+/// Char-based counterpart to `parse_name_and_rest`, matching through
+/// `confusable_skeleton` while returning the original (unfolded) substring.
+fn parse_name_and_rest_confusable(input: &str) -> Option<(String, &str)> {
+    if !input.chars().next().is_some_and(is_name_start_confusable) {
         return None;
     }
 
     let mut end_idx = 0;
-    for (idx, ch) in input.char_indices() {
-        if is_name_continue(ch) {
-            end_idx = idx + ch.len_utf8();
+    for ch in input.chars() {
+        if is_name_continue_confusable(ch) {
+            end_idx += ch.len_utf8();
         } else {
             break;
         }
@@ -609,8 +2807,10 @@ fn parse_name_and_rest(input: &str) -> Option<(String, &str)> {
     Some((name, rest))
 }
 
-fn parse_attrs(mut input: &str) -> HashMap<String, AttrValue> {
-    let mut attrs = HashMap::new();
+/// Char-based counterpart to `parse_attrs_multi`, used when
+/// `normalize_confusables` is on so fullwidth `＝`/quotes are recognized too.
+fn parse_attrs_multi_confusable(mut input: &str) -> HashMap<String, Vec<AttrValue>> {
+    let mut attrs: HashMap<String, Vec<AttrValue>> = HashMap::new();
     while !input.is_empty() {
         let trimmed = input.trim_start();
         if trimmed.is_empty() {
@@ -619,37 +2819,40 @@ fn parse_attrs(mut input: &str) -> HashMap<String, AttrValue> {
         let consumed_ws = input.len() - trimmed.len();
         input = &input[consumed_ws..];
 
-        let mut name = String::new();
         let mut idx = 0;
         for ch in input.chars() {
-            if is_name_continue(ch) {
-                name.push(ch);
+            if is_name_continue_confusable(ch) {
                 idx += ch.len_utf8();
             } else {
                 break;
             }
         }
-        if name.is_empty() {
+        if idx == 0 {
             break;
         }
+        let name = input[..idx].to_string();
         input = &input[idx..];
 
         let mut after_eq = input.trim_start();
         input = after_eq;
         let mut value: AttrValue = AttrValue::Bool(true);
-        if input.starts_with('=') {
-            input = &input[1..];
+        if input.chars().next().map(confusable_skeleton) == Some('=') {
+            let eq_len = input.chars().next().unwrap().len_utf8();
+            input = &input[eq_len..];
             after_eq = input.trim_start();
             input = after_eq;
 
             if let Some(first) = input.chars().next() {
-                if first == '"' || first == '\'' {
-                    let quote = first;
+                let folded_first = confusable_skeleton(first);
+                if folded_first == '"' || folded_first == '\'' {
                     input = &input[first.len_utf8()..];
-                    if let Some(pos) = input.find(quote) {
+                    if let Some((pos, closing)) = input
+                        .char_indices()
+                        .find(|(_, c)| confusable_skeleton(*c) == folded_first)
+                    {
                         let val = &input[..pos];
                         value = AttrValue::Str(val.to_string());
-                        input = &input[pos + quote.len_utf8()..];
+                        input = &input[pos + closing.len_utf8()..];
                     } else {
                         // Broken quote: run until end of tag text
                         value = AttrValue::Str(input.to_string());
@@ -658,7 +2861,8 @@ fn parse_attrs(mut input: &str) -> HashMap<String, AttrValue> {
                 } else {
                     let mut end = 0;
                     for (i, ch) in input.char_indices() {
-                        if ch.is_whitespace() || ch == '/' || ch == '>' {
+                        let folded = confusable_skeleton(ch);
+                        if ch.is_whitespace() || folded == '/' || folded == '>' {
                             break;
                         }
                         end = i + ch.len_utf8();
@@ -673,41 +2877,48 @@ fn parse_attrs(mut input: &str) -> HashMap<String, AttrValue> {
             }
         }
 
-        attrs.insert(name, value);
+        attrs.entry(name).or_default().push(value);
     }
 
     attrs
 }
 
-fn next_token_bounds(slice: &str) -> Option<(usize, usize)> {
-    let mut start = None;
-    let mut end = None;
-    for (idx, ch) in slice.char_indices() {
-        if ch.is_alphanumeric() {
-            if start.is_none() {
-                start = Some(idx);
-            }
-            end = Some(idx + ch.len_utf8());
-        } else if start.is_some() {
-            break;
-        }
-    }
-    match (start, end) {
-        (Some(s), Some(e)) => Some((s, e)),
-        _ => None,
-    }
-}
-
-fn is_name_start(ch: char) -> bool {
-    ch.is_ascii_alphabetic()
+fn is_trim_char(ch: char) -> bool {
+    ch.is_whitespace() || matches!(ch, ',' | '.' | ';' | ':' | '!' | '?' | ')' | '(')
 }
 
-fn is_name_continue(ch: char) -> bool {
-    ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | ':' | '.')
+/// Coerces `value` to `expected`, used by `attr_value_schema`. Leaves `value`
+/// untouched if it isn't a `Str`, or if it doesn't parse as `expected`.
+fn coerce_attr_value(value: AttrValue, expected: AttrValueType) -> AttrValue {
+    let AttrValue::Str(raw) = &value else {
+        return value;
+    };
+    match expected {
+        AttrValueType::Str => value,
+        AttrValueType::Int => raw.parse::<i64>().map(AttrValue::Int).unwrap_or(value),
+        AttrValueType::Float => raw.parse::<f64>().map(AttrValue::Float).unwrap_or(value),
+        AttrValueType::Bool => match raw.as_str() {
+            "true" => AttrValue::Bool(true),
+            "false" => AttrValue::Bool(false),
+            _ => value,
+        },
+    }
 }
 
-fn is_trim_char(ch: char) -> bool {
-    ch.is_whitespace() || matches!(ch, ',' | '.' | ';' | ':' | '!' | '?' | ')' | '(')
+/// Coerces `value` to `Int`, falling back to `Float`, used by
+/// `coerce_attr_values` when no `attr_value_schema` entry applies. Leaves
+/// `value` untouched if it isn't a `Str`, or if neither parse succeeds.
+fn coerce_attr_value_auto(value: AttrValue) -> AttrValue {
+    let AttrValue::Str(raw) = &value else {
+        return value;
+    };
+    if let Ok(i) = raw.parse::<i64>() {
+        return AttrValue::Int(i);
+    }
+    if let Ok(f) = raw.parse::<f64>() {
+        return AttrValue::Float(f);
+    }
+    value
 }
 
 #[cfg(test)]
@@ -718,11 +2929,25 @@ mod tests {
         result
             .segments
             .iter()
-            .filter(|seg| seg.annotations.iter().any(|a| a.tag == tag))
+            .filter(|seg| seg_has_tag(result, seg, tag))
             .map(|seg| seg.text.clone())
             .collect()
     }
 
+    fn seg_has_tag(result: &ParseResult, seg: &Segment, tag: &str) -> bool {
+        seg.annotations
+            .iter()
+            .any(|id| result.annotation(*id).tag == tag)
+    }
+
+    fn seg_annotation<'a>(result: &'a ParseResult, seg: &Segment, tag: &str) -> &'a Annotation {
+        seg.annotations
+            .iter()
+            .map(|id| result.annotation(*id))
+            .find(|a| a.tag == tag)
+            .unwrap()
+    }
+
     fn base_config() -> ParserConfig {
         let mut cfg = ParserConfig::default();
         cfg.recognized_tags = ["cite", "note", "todo", "claim", "risk", "code"]
@@ -746,13 +2971,8 @@ mod tests {
         let result = parse("We shipped <cite id=\"1\">last week</cite>.", &cfg);
         assert_eq!(result.text, "We shipped last week.");
         assert_eq!(result.segments.len(), 3);
-        assert!(
-            result.segments[1]
-                .annotations
-                .iter()
-                .any(|a| a.tag == "cite")
-        );
-        let ann = &result.segments[1].annotations[0];
+        assert!(seg_has_tag(&result, &result.segments[1], "cite"));
+        let ann = seg_annotation(&result, &result.segments[1], "cite");
         assert_eq!(ann.attrs.get("id"), Some(&AttrValue::Str("1".into())));
         assert_eq!(result.segments[1].text, "last week");
     }
@@ -768,7 +2988,7 @@ mod tests {
         let cite = result
             .segments
             .iter()
-            .find(|s| s.annotations.iter().any(|a| a.tag == "cite"))
+            .find(|s| seg_has_tag(&result, s, "cite"))
             .expect("cite span");
         assert!(cite.text.contains("We shipped last week"));
     }
@@ -780,9 +3000,9 @@ mod tests {
         let cite = result
             .segments
             .iter()
-            .find(|s| s.annotations.iter().any(|a| a.tag == "cite"))
+            .find(|s| seg_has_tag(&result, s, "cite"))
             .unwrap();
-        let ann = cite.annotations.iter().find(|a| a.tag == "cite").unwrap();
+        let ann = seg_annotation(&result, cite, "cite");
         assert_eq!(ann.attrs.get("id"), Some(&AttrValue::Str("1, 2".into())));
         assert_eq!(cite.text, "Evidence");
     }
@@ -817,15 +3037,11 @@ mod tests {
         let risk_segment = result
             .segments
             .iter()
-            .find(|s| s.annotations.iter().any(|a| a.tag == "risk"))
+            .find(|s| seg_has_tag(&result, s, "risk"))
             .expect("risk segment");
         assert_eq!(risk_segment.text, "delays <mystery>??</mystery> persist");
 
-        let ann = risk_segment
-            .annotations
-            .iter()
-            .find(|a| a.tag == "risk")
-            .unwrap();
+        let ann = seg_annotation(&result, risk_segment, "risk");
         assert_eq!(ann.attrs.get("level"), Some(&AttrValue::Str("high".into())));
     }
 
@@ -839,7 +3055,7 @@ mod tests {
         assert_eq!(result.text, "It works.");
         let claim = annotated_texts(&result, "claim");
         assert_eq!(claim, vec!["It works."]);
-        let attrs = &result.segments[0].annotations[0].attrs;
+        let attrs = &seg_annotation(&result, &result.segments[0], "claim").attrs;
         assert_eq!(attrs.get("id"), Some(&AttrValue::Str("7".into())));
         assert_eq!(
             attrs.get("confidence"),
@@ -858,7 +3074,7 @@ mod tests {
         assert_eq!(result.text, "Fix flaky test");
         let todo = annotated_texts(&result, "todo");
         assert_eq!(todo, vec!["Fix flaky test"]);
-        let attrs = &result.segments[0].annotations[0].attrs;
+        let attrs = &seg_annotation(&result, &result.segments[0], "todo").attrs;
         assert_eq!(attrs.get("urgent"), Some(&AttrValue::Bool(true)));
     }
 
@@ -879,11 +3095,9 @@ mod tests {
         assert_eq!(result.markers.len(), 1);
         let marker = &result.markers[0];
         assert_eq!(marker.pos, "Start ".len());
-        assert_eq!(marker.annotation.tag, "todo");
-        assert_eq!(
-            marker.annotation.attrs.get("id"),
-            Some(&AttrValue::Str("3".into()))
-        );
+        let ann = result.annotation(marker.annotation);
+        assert_eq!(ann.tag, "todo");
+        assert_eq!(ann.attrs.get("id"), Some(&AttrValue::Str("3".into())));
     }
 
     #[test]
@@ -958,97 +3172,220 @@ mod tests {
     fn unquoted_and_broken_quotes_recover() {
         let cfg = base_config();
         let one = parse("<cite id=1>Evidence</cite>", &cfg);
-        let ann = one.segments[0].annotations[0].attrs.get("id");
+        let ann = seg_annotation(&one, &one.segments[0], "cite").attrs.get("id");
         assert_eq!(ann, Some(&AttrValue::Str("1".into())));
 
         let broken_single = parse("<cite id='1,2>Evidence</cite>", &cfg);
-        let ann = broken_single.segments[0].annotations[0].attrs.get("id");
+        let ann = seg_annotation(&broken_single, &broken_single.segments[0], "cite")
+            .attrs
+            .get("id");
         assert_eq!(ann, Some(&AttrValue::Str("1,2".into())));
 
         let broken_double = parse("<cite id=\"3>Evidence</cite>", &cfg);
-        let ann = broken_double.segments[0].annotations[0].attrs.get("id");
+        let ann = seg_annotation(&broken_double, &broken_double.segments[0], "cite")
+            .attrs
+            .get("id");
         assert_eq!(ann, Some(&AttrValue::Str("3".into())));
 
         let broken_double_with_other_attr = parse("<cite id=\"4 ok=yes>Evidence</cite>", &cfg);
-        let ann = broken_double_with_other_attr.segments[0].annotations[0]
-            .attrs
-            .get("id");
+        let ann = seg_annotation(
+            &broken_double_with_other_attr,
+            &broken_double_with_other_attr.segments[0],
+            "cite",
+        )
+        .attrs
+        .get("id");
         assert_eq!(ann, Some(&AttrValue::Str("4 ok=yes".into())));
 
         let broken_single_with_other_attr = parse("<cite id='5 ok=yes>Evidence</cite>", &cfg);
-        let ann = broken_single_with_other_attr.segments[0].annotations[0]
-            .attrs
-            .get("id");
+        let ann = seg_annotation(
+            &broken_single_with_other_attr,
+            &broken_single_with_other_attr.segments[0],
+            "cite",
+        )
+        .attrs
+        .get("id");
         assert_eq!(ann, Some(&AttrValue::Str("5 ok=yes".into())));
     }
 
     #[test]
-    fn duplicate_attrs_last_wins() {
+    fn attr_diagnostics_off_by_default() {
         let cfg = base_config();
-        let result = parse("<cite id=1 id=2>Evidence</cite>", &cfg);
-        let ann = result.segments[0].annotations[0].attrs.get("id");
-        assert_eq!(ann, Some(&AttrValue::Str("2".into())));
+        let result = parse("<cite id='1,2>Evidence</cite>", &cfg);
+        assert_eq!(result.attr_diagnostics, None);
     }
 
     #[test]
-    #[ignore = "not implemented yet"]
-    fn duplicate_attrs_as_comma_list() {
-        let _cfg = base_config();
+    fn attr_diagnostics_reports_unterminated_quote() {
+        let mut cfg = base_config();
+        cfg.collect_attr_diagnostics = true;
+        let result = parse("<cite id='1,2>Evidence</cite>", &cfg);
+        let diags = result.attr_diagnostics.as_ref().unwrap();
+        assert!(diags.iter().any(|d| d.tag == "cite"
+            && d.attr.as_deref() == Some("id")
+            && d.kind == AttrDiagnosticKind::UnterminatedQuote));
     }
 
     #[test]
-    fn boolean_attr_without_value() {
-        let cfg = base_config();
-        let result = parse("<cite id>Evidence</cite>", &cfg);
-        let ann = result.segments[0].annotations[0].attrs.get("id");
-        assert_eq!(ann, Some(&AttrValue::Bool(true)));
+    fn attr_diagnostics_reports_missing_value() {
+        let mut cfg = base_config();
+        cfg.collect_attr_diagnostics = true;
+        let result = parse("<cite id=>Evidence</cite>", &cfg);
+        let diags = result.attr_diagnostics.as_ref().unwrap();
+        assert!(diags.iter().any(|d| d.tag == "cite"
+            && d.attr.as_deref() == Some("id")
+            && d.kind == AttrDiagnosticKind::MissingValue));
     }
 
     #[test]
-    fn missing_gt_treated_as_text() {
-        let cfg = base_config();
-        let result = parse("We shipped <cite id=1\nyesterday.", &cfg);
-        assert!(result.text.contains("<cite id=1\n"));
-        assert!(annotated_texts(&result, "cite").is_empty());
+    fn attr_diagnostics_reports_duplicate_attr() {
+        let mut cfg = base_config();
+        cfg.collect_attr_diagnostics = true;
+        let result = parse("<cite id=1 id=2>Evidence</cite>", &cfg);
+        let diags = result.attr_diagnostics.as_ref().unwrap();
+        assert!(diags.iter().any(|d| d.tag == "cite"
+            && d.attr.as_deref() == Some("id")
+            && d.kind == AttrDiagnosticKind::DuplicateAttr));
     }
 
     #[test]
-    fn unknown_tag_stripped_inner_preserved() {
-        let cfg = base_config();
-        let result = parse("Hello <weird x=1>world</weird>!", &cfg);
-        assert_eq!(result.text, "Hello world!");
-        assert!(
-            result
-                .segments
-                .iter()
-                .all(|s| s.annotations.iter().all(|a| a.tag != "weird"))
-        );
+    fn attr_diagnostics_reports_raw_angle_in_value() {
+        let mut cfg = base_config();
+        cfg.collect_attr_diagnostics = true;
+        let result = parse("<cite id=a<b>Evidence</cite>", &cfg);
+        let diags = result.attr_diagnostics.as_ref().unwrap();
+        assert!(diags.iter().any(|d| d.tag == "cite"
+            && d.attr.as_deref() == Some("id")
+            && d.kind == AttrDiagnosticKind::RawAngleInValue));
     }
 
     #[test]
-    fn reopening_same_tag_auto_close() {
+    fn duplicate_attrs_last_wins() {
         let cfg = base_config();
-        let result = parse("<cite id=1>One <cite id=2>Two</cite>", &cfg);
-        assert_eq!(result.text, "One Two");
-        let cites = annotated_texts(&result, "cite");
-        assert_eq!(cites, vec!["One ", "Two"]);
+        let result = parse("<cite id=1 id=2>Evidence</cite>", &cfg);
+        let ann = seg_annotation(&result, &result.segments[0], "cite")
+            .attrs
+            .get("id");
+        assert_eq!(ann, Some(&AttrValue::Str("2".into())));
     }
 
     #[test]
-    fn stray_closer_dropped_before_unclosed_tag() {
-        let cfg = base_config();
-        let result = parse("We shipped last week</cite><cite id=1>.", &cfg);
-        assert_eq!(result.text, "We shipped last week.");
-        let cites = annotated_texts(&result, "cite");
-        assert_eq!(cites, vec!["We shipped last week"]);
+    fn duplicate_attrs_as_comma_list() {
+        let mut cfg = base_config();
+        cfg.duplicate_attr_policy = DuplicateAttrPolicy::CommaList;
+        let result = parse("<cite id=1 id=2>Evidence</cite>", &cfg);
+        let ann = seg_annotation(&result, &result.segments[0], "cite")
+            .attrs
+            .get("id");
+        assert_eq!(ann, Some(&AttrValue::Str("1,2".into())));
     }
 
     #[test]
-    fn unclosed_cdata_runs_to_end_of_doc() {
+    fn duplicate_attrs_as_comma_list_produces_typed_list_when_coerced() {
         let mut cfg = base_config();
-        cfg.per_tag_recovery
-            .insert("code".into(), RecoveryStrategy::ForwardUntilTag);
-        let result = parse("<code><![CDATA[if (a < b) return;]]", &cfg);
+        cfg.duplicate_attr_policy = DuplicateAttrPolicy::CommaList;
+        cfg.coerce_attr_values = true;
+        let result = parse("<cite id=1 id=2>Evidence</cite>", &cfg);
+        let ann = seg_annotation(&result, &result.segments[0], "cite")
+            .attrs
+            .get("id");
+        assert_eq!(
+            ann,
+            Some(&AttrValue::List(vec![AttrValue::Int(1), AttrValue::Int(2)]))
+        );
+    }
+
+    #[test]
+    fn coerce_attr_values_converts_int_and_float() {
+        let mut cfg = base_config();
+        cfg.coerce_attr_values = true;
+        let result = parse("<claim id=7 confidence=0.62 source='internal'>It works.</claim>", &cfg);
+        let attrs = &seg_annotation(&result, &result.segments[0], "claim").attrs;
+        assert_eq!(attrs.get("id"), Some(&AttrValue::Int(7)));
+        assert_eq!(attrs.get("confidence"), Some(&AttrValue::Float(0.62)));
+        assert_eq!(
+            attrs.get("source"),
+            Some(&AttrValue::Str("internal".into()))
+        );
+    }
+
+    #[test]
+    fn coerce_attr_values_falls_back_to_str_on_broken_quote() {
+        let mut cfg = base_config();
+        cfg.coerce_attr_values = true;
+        let result = parse("<cite id=\"4 ok=yes>Evidence</cite>", &cfg);
+        let ann = seg_annotation(&result, &result.segments[0], "cite")
+            .attrs
+            .get("id");
+        assert_eq!(ann, Some(&AttrValue::Str("4 ok=yes".into())));
+    }
+
+    #[test]
+    fn attr_value_schema_coerces_one_attribute_only() {
+        let mut cfg = base_config();
+        cfg.attr_value_schema
+            .insert(("cite".to_string(), "id".to_string()), AttrValueType::Int);
+        let result = parse("<cite id=1 note=1>Evidence</cite>", &cfg);
+        let attrs = &seg_annotation(&result, &result.segments[0], "cite").attrs;
+        assert_eq!(attrs.get("id"), Some(&AttrValue::Int(1)));
+        assert_eq!(attrs.get("note"), Some(&AttrValue::Str("1".into())));
+    }
+
+    #[test]
+    fn boolean_attr_without_value() {
+        let cfg = base_config();
+        let result = parse("<cite id>Evidence</cite>", &cfg);
+        let ann = seg_annotation(&result, &result.segments[0], "cite")
+            .attrs
+            .get("id");
+        assert_eq!(ann, Some(&AttrValue::Bool(true)));
+    }
+
+    #[test]
+    fn missing_gt_treated_as_text() {
+        let cfg = base_config();
+        let result = parse("We shipped <cite id=1\nyesterday.", &cfg);
+        assert!(result.text.contains("<cite id=1\n"));
+        assert!(annotated_texts(&result, "cite").is_empty());
+    }
+
+    #[test]
+    fn unknown_tag_stripped_inner_preserved() {
+        let cfg = base_config();
+        let result = parse("Hello <weird x=1>world</weird>!", &cfg);
+        assert_eq!(result.text, "Hello world!");
+        assert!(
+            result
+                .segments
+                .iter()
+                .all(|s| !seg_has_tag(&result, s, "weird"))
+        );
+    }
+
+    #[test]
+    fn reopening_same_tag_auto_close() {
+        let cfg = base_config();
+        let result = parse("<cite id=1>One <cite id=2>Two</cite>", &cfg);
+        assert_eq!(result.text, "One Two");
+        let cites = annotated_texts(&result, "cite");
+        assert_eq!(cites, vec!["One ", "Two"]);
+    }
+
+    #[test]
+    fn stray_closer_dropped_before_unclosed_tag() {
+        let cfg = base_config();
+        let result = parse("We shipped last week</cite><cite id=1>.", &cfg);
+        assert_eq!(result.text, "We shipped last week.");
+        let cites = annotated_texts(&result, "cite");
+        assert_eq!(cites, vec!["We shipped last week"]);
+    }
+
+    #[test]
+    fn unclosed_cdata_runs_to_end_of_doc() {
+        let mut cfg = base_config();
+        cfg.per_tag_recovery
+            .insert("code".into(), RecoveryStrategy::ForwardUntilTag);
+        let result = parse("<code><![CDATA[if (a < b) return;]]", &cfg);
         assert_eq!(result.text, "if (a < b) return;]]");
         let code = annotated_texts(&result, "code");
         assert_eq!(code, vec!["if (a < b) return;]]"]);
@@ -1124,4 +3461,749 @@ mod tests {
         let cites = annotated_texts(&result, "cite");
         assert_eq!(cites, vec!["We shipped last week, "]);
     }
+
+    #[test]
+    fn positions_are_none_by_default() {
+        let cfg = base_config();
+        let result = parse("We shipped <cite id=\"1\">last week</cite>.", &cfg);
+        assert!(result.segments.iter().all(|s| s.span.is_none()));
+        assert!(
+            result
+                .segments
+                .iter()
+                .flat_map(|s| &s.annotations)
+                .all(|id| result.annotation(*id).span.is_none())
+        );
+    }
+
+    #[test]
+    fn tracked_positions_map_segments_to_original_input() {
+        let mut cfg = base_config();
+        cfg.track_positions = true;
+        let result = parse("We shipped <cite id=\"1\">last week</cite>.", &cfg);
+        let cite = result
+            .segments
+            .iter()
+            .find(|s| seg_has_tag(&result, s, "cite"))
+            .expect("cite segment");
+        let span = cite.span.expect("span tracked");
+        assert_eq!(&cite.text[..], "last week");
+        assert_eq!(&"We shipped <cite id=\"1\">last week</cite>."[span.start..span.end], "last week");
+        assert_eq!(span.start_line, 1);
+        assert_eq!(span.start_col, 25);
+    }
+
+    #[test]
+    fn tracked_positions_survive_stripped_markup() {
+        let mut cfg = base_config();
+        cfg.track_positions = true;
+        let input = "Line one\n<note>soon</note> more";
+        let result = parse(input, &cfg);
+        let note = result
+            .segments
+            .iter()
+            .find(|s| seg_has_tag(&result, s, "note"))
+            .expect("note segment");
+        let span = note.span.expect("span tracked");
+        assert_eq!(&input[span.start..span.end], "soon");
+        assert_eq!(span.start_line, 2);
+
+        let ann = seg_annotation(&result, note, "note")
+            .span
+            .expect("annotation span tracked");
+        assert_eq!(&input[ann.start..ann.end], "<note>");
+    }
+
+    #[test]
+    fn tracked_positions_on_marker() {
+        let mut cfg = base_config();
+        cfg.track_positions = true;
+        let input = "Start <todo id=3/> end";
+        let result = parse(input, &cfg);
+        let marker = &result.markers[0];
+        let span = marker.span.expect("marker span tracked");
+        assert_eq!(&input[span.start..span.end], "<todo id=3/>");
+    }
+
+    #[test]
+    fn line_col_locates_marker_and_segment_start_in_reconstructed_text() {
+        // No `track_positions` here: `line_col` works against the
+        // reconstructed `text` regardless of input-position tracking.
+        let cfg = base_config();
+        let input = "line one\nline two <todo id=3/> tail\nline three <cite id=1>cited</cite>";
+        let result = parse(input, &cfg);
+
+        let marker = &result.markers[0];
+        assert_eq!(result.line_col(marker.pos), (2, 10));
+
+        let mut pos = 0usize;
+        let mut cited_start = None;
+        for seg in &result.segments {
+            if seg_has_tag(&result, seg, "cite") {
+                cited_start = Some(pos);
+                break;
+            }
+            pos += seg.text.len();
+        }
+        let cited_start = cited_start.expect("cited segment present");
+        assert_eq!(result.line_col(cited_start), (3, 12));
+    }
+
+    #[test]
+    fn line_col_clamps_out_of_range_offsets_to_end_of_text() {
+        let cfg = base_config();
+        let result = parse("a\nbc", &cfg);
+        assert_eq!(result.line_col(result.text.len()), result.line_col(1000));
+    }
+
+    #[test]
+    fn line_col_rounds_down_a_mid_char_offset_instead_of_panicking() {
+        let cfg = base_config();
+        let result = parse("日本語テスト", &cfg);
+        // Byte 1 lands inside the first (3-byte) CJK character; rather than
+        // panicking on a non-boundary slice, this should round down to the
+        // start of that character.
+        assert_eq!(result.line_col(1), result.line_col(0));
+    }
+
+    #[test]
+    fn annotation_end_span_covers_closing_tag_token() {
+        let mut cfg = base_config();
+        cfg.track_positions = true;
+        let input = "We shipped <cite id=1>last week</cite>.";
+        let result = parse(input, &cfg);
+        let ann = seg_annotation(&result, &result.segments[1], "cite");
+        let end_span = ann.end_span.expect("end span tracked for explicit close");
+        assert_eq!(&input[end_span.start..end_span.end], "</cite>");
+    }
+
+    #[test]
+    fn annotation_end_span_absent_without_explicit_close() {
+        let mut cfg = base_config();
+        cfg.track_positions = true;
+        let result = parse("We shipped last week <cite id=1>.", &cfg);
+        let ann = seg_annotation(&result, &result.segments[0], "cite");
+        assert_eq!(ann.end_span, None);
+    }
+
+    #[test]
+    fn source_spans_map_output_offsets_back_to_input() {
+        let cfg = base_config();
+        let input = "We shipped <cite id=1>last week</cite>.";
+        let result = parse(input, &cfg);
+        assert_eq!(result.text, "We shipped last week.");
+
+        let last_week_start = result.text.find("last week").unwrap();
+        let input_start = result.output_to_input(last_week_start);
+        assert_eq!(&input[input_start..input_start + "last week".len()], "last week");
+
+        let period = result.text.len() - 1;
+        let input_period = result.output_to_input(period);
+        assert_eq!(&input[input_period..input_period + 1], ".");
+    }
+
+    #[test]
+    fn event_reader_yields_text_and_annotation_events() {
+        let cfg = base_config();
+        let input = "We shipped <cite id=\"1\">last week</cite>.";
+        let events: Vec<Event> = EventReader::new(input, &cfg).collect();
+
+        assert_eq!(events[0], Event::Text("We shipped "));
+        assert!(matches!(&events[1], Event::StartAnnotation(a) if a.tag == "cite"));
+        assert_eq!(events[2], Event::Text("last week"));
+        assert_eq!(
+            events[3],
+            Event::EndAnnotation {
+                tag: "cite".to_string()
+            }
+        );
+        assert_eq!(events[4], Event::Text("."));
+    }
+
+    #[test]
+    fn event_reader_marks_retro_line_as_recovered() {
+        let cfg = base_config();
+        let result = EventReader::new("We shipped last week <cite id=1>.", &cfg)
+            .any(|e| matches!(e, Event::Recovered(RecoveryStrategy::RetroLine)));
+        assert!(result);
+    }
+
+    #[test]
+    fn event_reader_yields_markers() {
+        let cfg = base_config();
+        let events: Vec<Event> = EventReader::new("Start <todo id=3/> end", &cfg).collect();
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, Event::Marker { annotation, .. } if annotation.tag == "todo"))
+        );
+    }
+
+    #[test]
+    fn parse_matches_folded_event_reader() {
+        let cfg = base_config();
+        for input in [
+            "We shipped <cite id=\"1\">last week</cite>.",
+            "We shipped last week <cite id=1> <note>Details...</note>",
+            "Alpha <note>bravo <cite id=9> charlie",
+            "Start <todo id=3/> end",
+        ] {
+            let eager = parse(input, &cfg);
+            let via_events: Vec<Event> = EventReader::new(input, &cfg).collect();
+            let folded = Parser::scan(input, &cfg).fold_events(via_events);
+            assert_eq!(eager, folded);
+        }
+    }
+
+    #[test]
+    fn streaming_parser_matches_batch_parse_for_arbitrary_chunking() {
+        let cfg = base_config();
+        let input = "We shipped last week <cite id=1> <note>Details...</note> done.";
+        let expected = parse(input, &cfg);
+
+        for chunk_size in [1, 2, 3, 5, 7, 64] {
+            let mut streaming = StreamingParser::new(cfg.clone());
+            for chunk in chunk_as_bytes(input, chunk_size) {
+                let _ = streaming.feed(chunk);
+            }
+            let result = streaming.finish();
+            assert_eq!(result, expected, "chunk_size={chunk_size}");
+        }
+    }
+
+    #[test]
+    fn streaming_parser_holds_back_tag_split_across_feeds() {
+        let cfg = base_config();
+        let mut streaming = StreamingParser::new(cfg);
+
+        let first = streaming.feed("Evidence <ci");
+        assert!(first.iter().any(|e| matches!(e, Event::Text(_))));
+        assert!(
+            !first
+                .iter()
+                .any(|e| matches!(e, Event::StartAnnotation(_)))
+        );
+
+        let second = streaming.feed("te id=1>here</cite>");
+        assert!(
+            second
+                .iter()
+                .any(|e| matches!(e, Event::StartAnnotation(a) if a.tag == "cite"))
+        );
+
+        let result = streaming.finish();
+        assert_eq!(result.text, "Evidence here");
+    }
+
+    #[test]
+    fn streaming_parser_holds_back_cdata_open_split_across_feeds() {
+        let cfg = base_config();
+        let mut streaming = StreamingParser::new(cfg);
+
+        // The `<![CDATA[` opener is complete but its `]]>` terminator hasn't
+        // arrived yet: `confirmed_prefix_len`'s CDATA branch must hold the
+        // whole thing back rather than letting the generic lone-`<` check
+        // (which would stop scanning at the first `<` inside the literal)
+        // emit part of it as text.
+        let first = streaming.feed("Raw <![CDATA[if (a");
+        assert_eq!(
+            first,
+            vec![Event::Text("Raw ")],
+            "unterminated CDATA opener must not be emitted as text yet"
+        );
+
+        // Once the terminator arrives, the CDATA content and the text after
+        // it share the same (empty) annotation stack, so they fold into a
+        // single finalized segment/event rather than splitting at the CDATA
+        // boundary the way the old per-call rescan used to.
+        let second = streaming.feed(" < b)]]> done");
+        assert_eq!(second, vec![Event::Text("if (a < b) done")]);
+
+        let result = streaming.finish();
+        assert_eq!(result.text, "Raw if (a < b) done");
+    }
+
+    #[test]
+    fn incremental_parser_holds_back_tag_split_across_feeds() {
+        let cfg = base_config();
+        let mut incremental = IncrementalParser::new(cfg);
+
+        let first = incremental.feed("Evidence <ci");
+        assert_eq!(first.consumed, "Evidence ".len());
+        assert!(
+            !first
+                .segments
+                .iter()
+                .any(|s| s.annotations.iter().any(|id| incremental.annotation(*id).tag == "cite"))
+        );
+
+        let second = incremental.feed("te id=1>here</cite>");
+        assert!(second.consumed > 0);
+        assert!(second.segments.iter().any(|s| s.text == "here"));
+
+        let result = incremental.finish();
+        assert_eq!(result.text, "Evidence here");
+    }
+
+    #[test]
+    fn incremental_parser_emits_segment_only_after_tag_closes() {
+        let cfg = base_config();
+        let mut incremental = IncrementalParser::new(cfg);
+
+        let first = incremental.feed("Intro <cite id=1>still open");
+        assert!(
+            !first
+                .segments
+                .iter()
+                .any(|s| s.annotations.iter().any(|id| incremental.annotation(*id).tag == "cite"))
+        );
+
+        let second = incremental.feed(" more</cite> tail");
+        assert!(
+            second
+                .segments
+                .iter()
+                .any(|s| s.annotations.iter().any(|id| incremental.annotation(*id).tag == "cite"))
+        );
+    }
+
+    #[test]
+    fn incremental_parser_holds_back_retro_line_candidate_until_finish() {
+        let cfg = base_config();
+        let mut incremental = IncrementalParser::new(cfg);
+
+        let first = incremental.feed("We shipped last week ");
+        assert!(first.segments.iter().any(|s| s.text.contains("We shipped")));
+
+        let second = incremental.feed("<cite id=1>");
+        assert!(second.segments.is_empty());
+
+        // No closing `</cite>` ever arrives, so `cite`'s RetroLine recovery
+        // can only resolve at `finish()`; the tag staying open must hold
+        // back every byte fed after it, not just the still-unterminated tag.
+        let third = incremental.feed(". more text after");
+        assert!(third.segments.is_empty());
+
+        let result = incremental.finish();
+        assert_eq!(result.text, "We shipped last week . more text after");
+        let cites = annotated_texts(&result, "cite");
+        assert_eq!(cites, vec!["We shipped last week"]);
+    }
+
+    #[test]
+    fn incremental_parser_finish_matches_batch_parse_for_arbitrary_chunking() {
+        let cfg = base_config();
+        let input = "We shipped last week <cite id=1> <note>Details...</note> done.";
+        let expected = parse(input, &cfg);
+
+        for chunk_size in [1, 2, 3, 5, 7, 64] {
+            let mut incremental = IncrementalParser::new(cfg.clone());
+            for chunk in chunk_as_bytes(input, chunk_size) {
+                let _ = incremental.feed(chunk);
+            }
+            let result = incremental.finish();
+            assert_eq!(result, expected, "chunk_size={chunk_size}");
+        }
+    }
+
+    #[test]
+    fn incremental_parser_holds_back_cdata_open_split_across_feeds() {
+        let cfg = base_config();
+        let mut incremental = IncrementalParser::new(cfg);
+
+        // Same boundary `confirmed_prefix_len` guards against for
+        // `StreamingParser`: an `<![CDATA[` opener with no `]]>` yet must not
+        // let its raw `<`/`>` leak out as (or be mistaken for) a tag.
+        let first = incremental.feed("Raw <![CDATA[if (a");
+        assert_eq!(first.segments.len(), 1);
+        assert_eq!(first.segments[0].text, "Raw ");
+
+        let second = incremental.feed(" < b)]]> done");
+        assert_eq!(second.segments.len(), 1);
+        assert_eq!(second.segments[0].text, "if (a < b) done");
+
+        let result = incremental.finish();
+        assert_eq!(result.text, "Raw if (a < b) done");
+    }
+
+    #[test]
+    fn unclosed_retro_line_emits_diagnostic() {
+        let cfg = base_config();
+        let result = parse("We shipped last week <cite id=1>", &cfg);
+        let diag = result
+            .diagnostics
+            .iter()
+            .find(|d| d.kind == DiagnosticKind::UnclosedTag && d.tag == "cite")
+            .expect("expected an unclosed tag diagnostic for cite");
+        assert_eq!(diag.severity, Severity::Warning);
+        assert_eq!(diag.strategy_applied, Some(RecoveryStrategy::RetroLine));
+    }
+
+    #[test]
+    fn stray_end_tag_emits_diagnostic() {
+        let cfg = base_config();
+        let result = parse("We shipped last week</cite><cite id=1>.", &cfg);
+        let diag = result
+            .diagnostics
+            .iter()
+            .find(|d| d.kind == DiagnosticKind::StrayEndTag)
+            .expect("expected a stray end tag diagnostic");
+        assert_eq!(diag.tag, "cite");
+        assert_eq!(diag.severity, Severity::Warning);
+    }
+
+    #[test]
+    fn unknown_tag_emits_info_diagnostic() {
+        let cfg = base_config();
+        let result = parse("Hello <weird x=1>world</weird>!", &cfg);
+        let diag = result
+            .diagnostics
+            .iter()
+            .find(|d| d.kind == DiagnosticKind::UnknownTag)
+            .expect("expected an unknown tag diagnostic");
+        assert_eq!(diag.tag, "weird");
+        assert_eq!(diag.severity, Severity::Info);
+    }
+
+    #[test]
+    fn ambiguous_autoclose_emits_diagnostic() {
+        let cfg = base_config();
+        let result = parse("<cite id=1>One <cite id=2>Two</cite>", &cfg);
+        let diag = result
+            .diagnostics
+            .iter()
+            .find(|d| d.kind == DiagnosticKind::AmbiguousAutoclose)
+            .expect("expected an ambiguous autoclose diagnostic");
+        assert_eq!(diag.tag, "cite");
+    }
+
+    #[test]
+    fn wikilink_profile_parses_cite_with_id_and_text() {
+        let mut cfg = base_config();
+        cfg.syntax = SyntaxProfile::Wikilink;
+        let result = parse("We shipped [[cite:1|last week]].", &cfg);
+        assert_eq!(result.text, "We shipped last week.");
+        let ann = result.segments.iter().find_map(|s| {
+            s.annotations
+                .iter()
+                .map(|id| result.annotation(*id))
+                .find(|a| a.tag == "cite")
+                .map(|a| (s.text.clone(), a.attrs.get("id").cloned()))
+        });
+        assert_eq!(
+            ann,
+            Some(("last week".to_string(), Some(AttrValue::Str("1".into()))))
+        );
+    }
+
+    #[test]
+    fn wikilink_profile_without_text_emits_marker() {
+        let mut cfg = base_config();
+        cfg.syntax = SyntaxProfile::Wikilink;
+        let result = parse("Done for today [[todo:2]]", &cfg);
+        assert_eq!(result.text, "Done for today ");
+        assert_eq!(result.markers.len(), 1);
+        assert_eq!(result.annotation(result.markers[0].annotation).tag, "todo");
+    }
+
+    #[test]
+    fn wikilink_profile_strips_unknown_tag_but_keeps_text() {
+        let mut cfg = base_config();
+        cfg.syntax = SyntaxProfile::Wikilink;
+        let result = parse("See [[weird:1|over here]] please", &cfg);
+        assert_eq!(result.text, "See over here please");
+        assert!(
+            result
+                .segments
+                .iter()
+                .all(|s| !seg_has_tag(&result, s, "weird"))
+        );
+    }
+
+    #[test]
+    fn prefixed_tag_recognized_by_local_name() {
+        let cfg = base_config();
+        let result = parse("<doc:cite id=1>Evidence</doc:cite>", &cfg);
+        let ann = result.annotation(result.segments[0].annotations[0]);
+        assert_eq!(ann.tag, "cite");
+        assert_eq!(ann.prefix, Some("doc".to_string()));
+    }
+
+    #[test]
+    fn prefixed_tag_uses_local_name_recovery_strategy() {
+        let cfg = base_config();
+        let result = parse("We shipped last week <doc:cite id=1>", &cfg);
+        let cites = annotated_texts(&result, "cite");
+        assert_eq!(cites, vec!["We shipped last week"]);
+    }
+
+    #[test]
+    fn full_name_mode_requires_qualified_recognized_tag() {
+        let mut cfg = base_config();
+        cfg.prefix_match_mode = PrefixMatchMode::FullName;
+        cfg.recognized_tags = ["doc:cite"].iter().map(|s| s.to_string()).collect();
+        cfg.per_tag_recovery
+            .insert("cite".into(), RecoveryStrategy::RetroLine);
+
+        let qualified = parse("Evidence <doc:cite id=1>", &cfg);
+        assert_eq!(annotated_texts(&qualified, "cite"), vec!["Evidence"]);
+
+        let unqualified = parse("Evidence <cite id=1>", &cfg);
+        assert!(annotated_texts(&unqualified, "cite").is_empty());
+    }
+
+    #[test]
+    fn full_name_mode_is_case_insensitive_on_the_prefix() {
+        let mut cfg = base_config();
+        cfg.prefix_match_mode = PrefixMatchMode::FullName;
+        cfg.case_sensitive_tags = false;
+        cfg.recognized_tags = ["doc:cite"].iter().map(|s| s.to_string()).collect();
+        cfg.per_tag_recovery
+            .insert("cite".into(), RecoveryStrategy::RetroLine);
+
+        let mixed_case_prefix = parse("Evidence <DOC:cite id=1>", &cfg);
+        assert_eq!(annotated_texts(&mixed_case_prefix, "cite"), vec!["Evidence"]);
+    }
+
+    #[test]
+    fn prefix_whitelist_mode_rejects_unlisted_prefix() {
+        let mut cfg = base_config();
+        cfg.prefix_match_mode = PrefixMatchMode::PrefixWhitelist;
+        cfg.recognized_prefixes = ["doc"].iter().map(|s| s.to_string()).collect();
+
+        let allowed = parse("Evidence <doc:cite id=1>", &cfg);
+        assert_eq!(annotated_texts(&allowed, "cite"), vec!["Evidence"]);
+
+        let rejected = parse("Evidence <src:cite id=1>", &cfg);
+        assert!(annotated_texts(&rejected, "cite").is_empty());
+        assert!(
+            rejected
+                .diagnostics
+                .iter()
+                .any(|d| d.kind == DiagnosticKind::UnknownTag && d.tag == "cite")
+        );
+    }
+
+    fn chunk_as_bytes(input: &str, size: usize) -> Vec<&str> {
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        let bytes = input.as_bytes();
+        while start < bytes.len() {
+            let mut end = (start + size).min(bytes.len());
+            while !input.is_char_boundary(end) {
+                end -= 1;
+            }
+            chunks.push(&input[start..end]);
+            start = end;
+        }
+        chunks
+    }
+
+    #[test]
+    fn to_markup_reproduces_adjacent_spans() {
+        let cfg = base_config();
+        let input = "<cite id=1>A</cite><cite id=2>B</cite>";
+        let result = parse(input, &cfg);
+        assert_eq!(result.to_markup(&cfg), input);
+    }
+
+    #[test]
+    fn to_markup_round_trips_self_closing_marker() {
+        let cfg = base_config();
+        let input = "Start <todo id=3/> end";
+        let result = parse(input, &cfg);
+        assert_eq!(result.to_markup(&cfg), input);
+    }
+
+    #[test]
+    fn to_markup_cdata_wraps_raw_angle_brackets() {
+        let cfg = base_config();
+        let input = "<code><![CDATA[if (a < b) { return a > 0; }]]></code>";
+        let result = parse(input, &cfg);
+        assert_eq!(result.to_markup(&cfg), input);
+    }
+
+    #[test]
+    fn to_markup_quotes_attribute_values_with_spaces() {
+        let cfg = base_config();
+        let input = "<cite id=\"5 ok=yes\">Evidence</cite>";
+        let result = parse(input, &cfg);
+        assert_eq!(result.to_markup(&cfg), input);
+    }
+
+    #[test]
+    fn to_markup_reopens_nested_tags_outer_to_inner() {
+        let mut cfg = base_config();
+        cfg.autoclose_on_any_tag = false;
+        cfg.autoclose_on_same_tag = false;
+        let input = "<note><cite id=1>text</cite></note>";
+        let result = parse(input, &cfg);
+        assert_eq!(result.to_markup(&cfg), input);
+    }
+
+    #[test]
+    fn to_markup_wikilink_profile_round_trips_span_and_marker() {
+        let mut cfg = base_config();
+        cfg.syntax = SyntaxProfile::Wikilink;
+        let input = "See [[cite:1|the source]] and [[todo:2]] later.";
+        let result = parse(input, &cfg);
+        assert_eq!(result.to_markup(&cfg), input);
+    }
+
+    #[test]
+    fn normalize_confusables_recognizes_fullwidth_brackets() {
+        let mut cfg = base_config();
+        cfg.normalize_confusables = true;
+        let result = parse("We shipped ＜cite id=1＞last week＜／cite＞.", &cfg);
+        assert_eq!(result.text, "We shipped last week.");
+        assert!(seg_has_tag(&result, &result.segments[1], "cite"));
+    }
+
+    #[test]
+    fn without_normalize_confusables_fullwidth_brackets_are_plain_text() {
+        let cfg = base_config();
+        let input = "We shipped ＜cite id=1＞last week＜／cite＞.";
+        let result = parse(input, &cfg);
+        assert_eq!(result.text, input);
+        assert!(result.segments.iter().all(|s| s.annotations.is_empty()));
+    }
+
+    #[test]
+    fn normalize_confusables_folds_homoglyph_tag_name() {
+        let mut cfg = base_config();
+        cfg.normalize_confusables = true;
+        // The tag name here is spelled with Cyrillic `с`, not Latin `c`;
+        // `resolve_tag_name` folds it to the canonical ASCII spelling, which
+        // is what ends up in `Annotation::tag`.
+        let result = parse("<сite id=1>last week</сite>.", &cfg);
+        assert_eq!(result.text, "last week.");
+        assert_eq!(result.segments[0].annotations.len(), 1);
+        assert_eq!(result.annotation(result.segments[0].annotations[0]).tag, "cite");
+    }
+
+    #[test]
+    fn tag_case_style_collapses_style_variants_to_one_canonical_tag() {
+        let mut cfg = ParserConfig::default();
+        cfg.recognized_tags = ["bold_text"].iter().map(|s| s.to_string()).collect();
+        cfg.case_sensitive_tags = false;
+        cfg.tag_case_style = Some(TagCaseStyle::Snake);
+        cfg.per_tag_recovery
+            .insert("bold_text".into(), RecoveryStrategy::ForwardUntilTag);
+
+        for input in [
+            "<BoldText>a</BoldText>",
+            "<bold-text>a</bold-text>",
+            "<bold_text>a</bold_text>",
+        ] {
+            let result = parse(input, &cfg);
+            assert_eq!(result.text, "a", "input: {input}");
+            assert_eq!(result.segments[0].annotations.len(), 1, "input: {input}");
+            assert_eq!(
+                result.annotation(result.segments[0].annotations[0]).tag,
+                "bold_text",
+                "input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn tag_aliases_map_arbitrary_spelling_to_canonical_tag() {
+        let mut cfg = ParserConfig::default();
+        cfg.recognized_tags = ["cite"].iter().map(|s| s.to_string()).collect();
+        cfg.tag_aliases
+            .insert("reference".to_string(), "cite".to_string());
+        cfg.per_tag_recovery
+            .insert("cite".into(), RecoveryStrategy::RetroLine);
+
+        let result = parse("evidence <reference id=1>", &cfg);
+        assert_eq!(result.text, "evidence ");
+        assert_eq!(result.segments[0].annotations.len(), 1);
+        assert_eq!(
+            result.annotation(result.segments[0].annotations[0]).tag,
+            "cite"
+        );
+    }
+
+    #[test]
+    fn tag_case_style_mismatched_close_tag_still_matches_open() {
+        let mut cfg = ParserConfig::default();
+        cfg.recognized_tags = ["bold_text"].iter().map(|s| s.to_string()).collect();
+        cfg.tag_case_style = Some(TagCaseStyle::Snake);
+        cfg.per_tag_recovery
+            .insert("bold_text".into(), RecoveryStrategy::ForwardUntilTag);
+
+        let result = parse("<BoldText>a</bold-text>", &cfg);
+        assert_eq!(result.text, "a");
+        assert_eq!(result.segments[0].annotations.len(), 1);
+    }
+
+    #[test]
+    fn normalize_confusables_applies_folding_before_lowercasing() {
+        let mut cfg = base_config();
+        cfg.normalize_confusables = true;
+        cfg.case_sensitive_tags = false;
+        // Fullwidth letters fold to their (uppercase) ASCII counterparts
+        // before the lowercasing step runs, so an uppercase fullwidth
+        // spelling still matches the lowercase `recognized_tags` entry.
+        let result = parse("＜ＣＩＴＥ id=1＞last week＜／ＣＩＴＥ＞.", &cfg);
+        assert_eq!(result.text, "last week.");
+        assert_eq!(result.segments[0].annotations.len(), 1);
+    }
+
+    #[test]
+    fn resolve_references_groups_repeated_citations_by_key() {
+        let cfg = base_config();
+        let result = parse(
+            "We shipped <cite id=1>last week</cite> and again <cite id=1>this week</cite>.",
+            &cfg,
+        );
+        let refs = result.resolve_references("cite", "id");
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].key, "1");
+        assert_eq!(refs[0].spans.len(), 2);
+    }
+
+    #[test]
+    fn resolve_references_keeps_distinct_keys_separate_and_in_first_seen_order() {
+        let cfg = base_config();
+        let result = parse("<cite id=2>second</cite> before <cite id=1>first</cite>.", &cfg);
+        let refs = result.resolve_references("cite", "id");
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].key, "2");
+        assert_eq!(refs[1].key, "1");
+    }
+
+    #[test]
+    fn resolve_references_ignores_annotations_missing_the_key_attr() {
+        let cfg = base_config();
+        let result = parse("<cite>no id here</cite>", &cfg);
+        assert!(result.resolve_references("cite", "id").is_empty());
+    }
+
+    #[test]
+    fn resolve_references_merges_attrs_and_includes_marker_spans() {
+        let mut cfg = base_config();
+        cfg.recognized_tags.insert("ref".into());
+        let result = parse("See <ref id=1 page=3/> and <ref id=1 note=\"see also\"/>.", &cfg);
+        let refs = result.resolve_references("ref", "id");
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].spans.len(), 2);
+        assert_eq!(refs[0].attrs.get("page"), Some(&AttrValue::Str("3".into())));
+        assert_eq!(
+            refs[0].attrs.get("note"),
+            Some(&AttrValue::Str("see also".into()))
+        );
+    }
+
+    #[test]
+    fn byte_scan_preserves_multibyte_text_around_tags() {
+        // `run_angle`'s hot loop finds `<` with a byte search; this only
+        // stays correct if multi-byte UTF-8 text between tags is left
+        // untouched rather than iterated/re-sliced per char.
+        let cfg = base_config();
+        let result = parse("日本語 <cite id=1>café ☕ 北京</cite> 続き", &cfg);
+        assert_eq!(result.text, "日本語 café ☕ 北京 続き");
+        assert!(seg_has_tag(&result, &result.segments[1], "cite"));
+        assert_eq!(result.segments[1].text, "café ☕ 北京");
+    }
 }
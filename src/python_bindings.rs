@@ -1,11 +1,98 @@
 #![allow(unsafe_op_in_unsafe_fn)]
 
+use std::collections::HashMap;
+
 use crate::{
-    Annotation, AttrValue, Marker, ParseResult, ParserConfig, RecoveryStrategy, Segment,
-    UnknownMode, parse,
+    Annotation, AnnotationId, AttrDiagnostic, AttrDiagnosticKind, AttrValue, Diagnostic,
+    IncrementalParser, Marker, ParseResult, ParserConfig, RecoveryStrategy, Reference, Segment,
+    Severity, TagCaseStyle, UnknownMode, parse,
 };
 use pyo3::prelude::*;
-use pyo3::types::PyType;
+use pyo3::types::{PyDict, PyList, PyType};
+
+fn attr_value_to_py(py: Python<'_>, value: &AttrValue) -> PyResult<PyObject> {
+    Ok(match value {
+        AttrValue::Bool(b) => b.into_py(py),
+        AttrValue::Str(s) => s.into_py(py),
+        AttrValue::Int(i) => i.into_py(py),
+        AttrValue::Float(f) => f.into_py(py),
+        AttrValue::List(items) => {
+            let list = PyList::empty_bound(py);
+            for item in items {
+                list.append(attr_value_to_py(py, item)?)?;
+            }
+            list.into_py(py)
+        }
+    })
+}
+
+/// Reverse of `attr_value_to_py`, for `PyParseResult::from_dict`. Checked in
+/// `bool`/`int`/`float`/`str`/`list` order, since a Python `bool` would
+/// otherwise also extract cleanly as an `int`.
+fn py_to_attr_value(value: &Bound<'_, PyAny>) -> PyResult<AttrValue> {
+    if let Ok(b) = value.extract::<bool>() {
+        return Ok(AttrValue::Bool(b));
+    }
+    if let Ok(i) = value.extract::<i64>() {
+        return Ok(AttrValue::Int(i));
+    }
+    if let Ok(f) = value.extract::<f64>() {
+        return Ok(AttrValue::Float(f));
+    }
+    if let Ok(s) = value.extract::<String>() {
+        return Ok(AttrValue::Str(s));
+    }
+    if let Ok(list) = value.downcast::<PyList>() {
+        let items = list
+            .iter()
+            .map(|item| py_to_attr_value(&item))
+            .collect::<PyResult<Vec<_>>>()?;
+        return Ok(AttrValue::List(items));
+    }
+    Err(pyo3::exceptions::PyTypeError::new_err(
+        "unsupported attribute value type",
+    ))
+}
+
+/// Builds the `{"tag", "prefix", "attrs"}` dict `PyParseResult::to_dict`
+/// nests inside each segment/marker entry.
+fn annotation_to_dict(py: Python<'_>, ann: &Annotation) -> PyResult<PyObject> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("tag", &ann.tag)?;
+    dict.set_item("prefix", &ann.prefix)?;
+    let attrs = PyDict::new_bound(py);
+    for (k, v) in &ann.attrs {
+        attrs.set_item(k, attr_value_to_py(py, v)?)?;
+    }
+    dict.set_item("attrs", attrs)?;
+    Ok(dict.into_py(py))
+}
+
+/// Reverse of `annotation_to_dict`, for `PyParseResult::from_dict`.
+fn annotation_from_dict(dict: &Bound<'_, PyDict>) -> PyResult<Annotation> {
+    let tag: String = dict
+        .get_item("tag")?
+        .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err("annotation missing 'tag'"))?
+        .extract()?;
+    let prefix: Option<String> = match dict.get_item("prefix")? {
+        Some(v) => v.extract()?,
+        None => None,
+    };
+    let mut attrs = HashMap::new();
+    if let Some(attrs_obj) = dict.get_item("attrs")? {
+        let attrs_dict: &Bound<PyDict> = attrs_obj.downcast()?;
+        for (k, v) in attrs_dict.iter() {
+            attrs.insert(k.extract::<String>()?, py_to_attr_value(&v)?);
+        }
+    }
+    Ok(Annotation {
+        tag,
+        prefix,
+        attrs,
+        span: None,
+        end_span: None,
+    })
+}
 
 #[pyclass(name = "Annotation")]
 #[derive(Clone)]
@@ -17,7 +104,7 @@ pub struct PyAnnotation {
 impl PyAnnotation {
     #[classattr]
     const __doc__: &'static str =
-        "Annotation(tag: str, attrs: dict[str, bool | str]) -> annotation attached to a span.";
+        "Annotation(tag: str, attrs: dict[str, bool | str | int | float | list]) -> annotation attached to a span.";
 
     #[getter]
     fn tag(&self) -> &str {
@@ -26,12 +113,9 @@ impl PyAnnotation {
 
     #[getter]
     fn attrs<'py>(&self, py: Python<'py>) -> PyResult<PyObject> {
-        let dict = pyo3::types::PyDict::new_bound(py);
+        let dict = PyDict::new_bound(py);
         for (k, v) in &self.inner.attrs {
-            match v {
-                AttrValue::Bool(b) => dict.set_item(k, *b)?,
-                AttrValue::Str(s) => dict.set_item(k, s)?,
-            }
+            dict.set_item(k, attr_value_to_py(py, v)?)?;
         }
         Ok(dict.into_py(py))
     }
@@ -44,10 +128,32 @@ impl PyAnnotation {
     }
 }
 
+/// A contiguous run of text plus any annotations covering it. Unlike the
+/// core [`Segment`], whose `annotations` are [`AnnotationId`]s into a
+/// [`ParseResult`]/[`IncrementalParser`] arena, each `annotations` entry
+/// here is already resolved to a standalone `Annotation`, since a Python
+/// `Segment` doesn't carry a reference back to the arena it came from.
 #[pyclass(name = "Segment")]
 #[derive(Clone)]
 pub struct PySegment {
-    inner: Segment,
+    text: String,
+    annotations: Vec<Annotation>,
+}
+
+impl PySegment {
+    fn from_core<'a>(
+        segment: &Segment,
+        resolve: impl Fn(AnnotationId) -> &'a Annotation,
+    ) -> Self {
+        Self {
+            text: segment.text.clone(),
+            annotations: segment
+                .annotations
+                .iter()
+                .map(|id| resolve(*id).clone())
+                .collect(),
+        }
+    }
 }
 
 #[pymethods]
@@ -57,13 +163,12 @@ impl PySegment {
 
     #[getter]
     fn text(&self) -> &str {
-        &self.inner.text
+        &self.text
     }
 
     #[getter]
     fn annotations<'py>(&self, py: Python<'py>) -> PyResult<Vec<Py<PyAnnotation>>> {
-        self.inner
-            .annotations
+        self.annotations
             .iter()
             .cloned()
             .map(|a| Py::new(py, PyAnnotation { inner: a }))
@@ -73,9 +178,8 @@ impl PySegment {
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!(
             "Segment(text='{}', annotations={})",
-            self.inner.text.replace('\'', "\""),
-            self.inner
-                .annotations
+            self.text.replace('\'', "\""),
+            self.annotations
                 .iter()
                 .map(|a| format!("Annotation(tag='{}')", a.tag))
                 .collect::<Vec<_>>()
@@ -84,10 +188,22 @@ impl PySegment {
     }
 }
 
+/// A zero-width marker produced by self-closing tags. See [`PySegment`] for
+/// why `annotation` is an owned `Annotation` rather than an id.
 #[pyclass(name = "Marker")]
 #[derive(Clone)]
 pub struct PyMarker {
-    inner: Marker,
+    pos: usize,
+    annotation: Annotation,
+}
+
+impl PyMarker {
+    fn from_core<'a>(marker: &Marker, resolve: impl Fn(AnnotationId) -> &'a Annotation) -> Self {
+        Self {
+            pos: marker.pos,
+            annotation: resolve(marker.annotation).clone(),
+        }
+    }
 }
 
 #[pymethods]
@@ -98,7 +214,7 @@ impl PyMarker {
 
     #[getter]
     fn pos(&self) -> usize {
-        self.inner.pos
+        self.pos
     }
 
     #[getter]
@@ -106,7 +222,7 @@ impl PyMarker {
         Py::new(
             py,
             PyAnnotation {
-                inner: self.inner.annotation.clone(),
+                inner: self.annotation.clone(),
             },
         )
     }
@@ -114,21 +230,209 @@ impl PyMarker {
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!(
             "Marker(pos={}, annotation=Annotation(tag='{}'))",
-            self.inner.pos, self.inner.annotation.tag
+            self.pos, self.annotation.tag
         ))
     }
 }
 
+/// Stringifies a `RecoveryStrategy` using the same vocabulary
+/// `with_recovery_strategy` accepts, so round-tripping through Python is
+/// lossless.
+fn recovery_strategy_name(strategy: &RecoveryStrategy) -> &'static str {
+    match strategy {
+        RecoveryStrategy::RetroLine => "retro_line",
+        RecoveryStrategy::ForwardUntilTag => "forward_until_tag",
+        RecoveryStrategy::ForwardUntilNewline => "forward_until_newline",
+        RecoveryStrategy::ForwardNextToken => "forward_next_token",
+        RecoveryStrategy::Noop => "noop",
+    }
+}
+
+/// A recorded recovery action: an unknown tag, an auto-close, or a recovery
+/// strategy firing for an unclosed tag. `pos`/`end` are `None` unless the
+/// config that produced this `ParseResult` had `track_positions` on.
+#[pyclass(name = "Diagnostic")]
+#[derive(Clone)]
+pub struct PyDiagnostic {
+    inner: Diagnostic,
+}
+
+#[pymethods]
+impl PyDiagnostic {
+    #[classattr]
+    const __doc__: &'static str =
+        "Diagnostic(pos: int | None, end: int | None, tag: str, strategy: str | None, severity: str, message: str).";
+
+    #[getter]
+    fn pos(&self) -> Option<usize> {
+        self.inner.span.map(|s| s.start)
+    }
+
+    #[getter]
+    fn end(&self) -> Option<usize> {
+        self.inner.span.map(|s| s.end)
+    }
+
+    #[getter]
+    fn tag(&self) -> &str {
+        &self.inner.tag
+    }
+
+    #[getter]
+    fn strategy(&self) -> Option<&'static str> {
+        self.inner.strategy_applied.as_ref().map(recovery_strategy_name)
+    }
+
+    #[getter]
+    fn severity(&self) -> &'static str {
+        match self.inner.severity {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+        }
+    }
+
+    #[getter]
+    fn message(&self) -> &str {
+        &self.inner.message
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Diagnostic(tag='{}', severity='{}', message='{}')",
+            self.inner.tag,
+            self.severity(),
+            self.inner.message
+        )
+    }
+}
+
+/// A recoverable attribute-text problem (unterminated quote, dangling `=`,
+/// duplicate name, raw `<` in a value). Only populated when the config that
+/// produced this `ParseResult` had `with_collect_attr_diagnostics(True)` set.
+#[pyclass(name = "AttrDiagnostic")]
+#[derive(Clone)]
+pub struct PyAttrDiagnostic {
+    inner: AttrDiagnostic,
+}
+
+#[pymethods]
+impl PyAttrDiagnostic {
+    #[classattr]
+    const __doc__: &'static str = "AttrDiagnostic(tag: str, attr: str | None, kind: str).";
+
+    #[getter]
+    fn tag(&self) -> &str {
+        &self.inner.tag
+    }
+
+    #[getter]
+    fn attr(&self) -> Option<&str> {
+        self.inner.attr.as_deref()
+    }
+
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.inner.kind {
+            AttrDiagnosticKind::UnterminatedQuote => "unterminated_quote",
+            AttrDiagnosticKind::MissingValue => "missing_value",
+            AttrDiagnosticKind::DuplicateAttr => "duplicate_attr",
+            AttrDiagnosticKind::RawAngleInValue => "raw_angle_in_value",
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AttrDiagnostic(tag='{}', attr={:?}, kind='{}')",
+            self.inner.tag,
+            self.inner.attr,
+            self.kind()
+        )
+    }
+}
+
+/// A deduplicated citation target: every span citing `key`, with attrs
+/// merged across occurrences. Only populated when the config that produced
+/// this `ParseResult` had `with_resolve_references(True)` set.
+#[pyclass(name = "Reference")]
+#[derive(Clone)]
+pub struct PyReference {
+    inner: Reference,
+}
+
+#[pymethods]
+impl PyReference {
+    #[classattr]
+    const __doc__: &'static str =
+        "Reference(key: str, spans: list[tuple[int, int]], attrs: dict[str, bool | str | int | float | list]).";
+
+    #[getter]
+    fn key(&self) -> &str {
+        &self.inner.key
+    }
+
+    #[getter]
+    fn spans(&self) -> Vec<(usize, usize)> {
+        self.inner
+            .spans
+            .iter()
+            .map(|s| (s.start, s.end))
+            .collect()
+    }
+
+    #[getter]
+    fn attrs<'py>(&self, py: Python<'py>) -> PyResult<PyObject> {
+        let dict = PyDict::new_bound(py);
+        for (k, v) in &self.inner.attrs {
+            dict.set_item(k, attr_value_to_py(py, v)?)?;
+        }
+        Ok(dict.into_py(py))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Reference(key='{}', spans={})",
+            self.inner.key,
+            self.inner.spans.len()
+        )
+    }
+}
+
+/// Citation tag and key attrs `resolve_citation_references` checks, in
+/// order: the first key attr that groups at least one citation is used for
+/// the whole result, so annotations carrying only a later key attr are not
+/// grouped when an earlier one matched.
+const CITATION_TAG: &str = "cite";
+const CITATION_KEY_ATTRS: [&str; 2] = ["id", "ref"];
+
+/// Resolves citation references for `with_resolve_references(True)`, trying
+/// each of `CITATION_KEY_ATTRS` in turn and keeping whichever key attr
+/// actually groups citations (callers use `id`, or `ref` as a fallback name
+/// for the same thing — not both at once).
+fn resolve_citation_references(result: &ParseResult) -> Vec<Reference> {
+    for key_attr in CITATION_KEY_ATTRS {
+        let refs = result.resolve_references(CITATION_TAG, key_attr);
+        if !refs.is_empty() {
+            return refs;
+        }
+    }
+    Vec::new()
+}
+
 #[pyclass(name = "ParseResult")]
 pub struct PyParseResult {
     inner: ParseResult,
+    references: Vec<Reference>,
 }
 
 #[pymethods]
 impl PyParseResult {
     #[classattr]
     const __doc__: &'static str =
-        "ParseResult(text: str, segments: list[Segment], markers: list[Marker]).";
+        "ParseResult(text: str, segments: list[Segment], markers: list[Marker], diagnostics: list[Diagnostic], \
+         attr_diagnostics: list[AttrDiagnostic] | None, references: list[Reference]). \
+         to_dict()/to_json() serialize for round-tripping via from_dict(); to_kindaxml() re-emits canonical \
+         markup. attr_diagnostics is None unless the config used with_collect_attr_diagnostics(True); \
+         references is only populated when the config used with_resolve_references(True).";
 
     #[getter]
     fn text(&self) -> &str {
@@ -140,8 +444,8 @@ impl PyParseResult {
         self.inner
             .segments
             .iter()
-            .cloned()
-            .map(|s| Py::new(py, PySegment { inner: s }))
+            .map(|s| PySegment::from_core(s, |id| self.inner.annotation(id)))
+            .map(|s| Py::new(py, s))
             .collect()
     }
 
@@ -150,24 +454,196 @@ impl PyParseResult {
         self.inner
             .markers
             .iter()
+            .map(|m| PyMarker::from_core(m, |id| self.inner.annotation(id)))
+            .map(|m| Py::new(py, m))
+            .collect()
+    }
+
+    #[getter]
+    fn diagnostics<'py>(&self, py: Python<'py>) -> PyResult<Vec<Py<PyDiagnostic>>> {
+        self.inner
+            .diagnostics
+            .iter()
             .cloned()
-            .map(|m| Py::new(py, PyMarker { inner: m }))
+            .map(|d| Py::new(py, PyDiagnostic { inner: d }))
             .collect()
     }
 
+    /// Recoverable attribute-text problems found while parsing; `None`
+    /// unless the config used `with_collect_attr_diagnostics(True)`.
+    #[getter]
+    fn attr_diagnostics<'py>(
+        &self,
+        py: Python<'py>,
+    ) -> PyResult<Option<Vec<Py<PyAttrDiagnostic>>>> {
+        let Some(issues) = self.inner.attr_diagnostics.as_ref() else {
+            return Ok(None);
+        };
+        let out: PyResult<Vec<Py<PyAttrDiagnostic>>> = issues
+            .iter()
+            .cloned()
+            .map(|d| Py::new(py, PyAttrDiagnostic { inner: d }))
+            .collect();
+        out.map(Some)
+    }
+
+    /// Deduplicated citation index; empty unless the config used
+    /// `with_resolve_references(True)`.
+    #[getter]
+    fn references<'py>(&self, py: Python<'py>) -> PyResult<Vec<Py<PyReference>>> {
+        self.references
+            .iter()
+            .cloned()
+            .map(|r| Py::new(py, PyReference { inner: r }))
+            .collect()
+    }
+
+    /// Plain nested dict (`text`, `segments` with byte ranges into `text`
+    /// and their annotations, `markers`) suitable for `json.dumps` or
+    /// reconstructing via `from_dict` without re-parsing.
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("text", &self.inner.text)?;
+
+        let segments = PyList::empty_bound(py);
+        let mut pos = 0usize;
+        for seg in &self.inner.segments {
+            let seg_dict = PyDict::new_bound(py);
+            seg_dict.set_item("text", &seg.text)?;
+            seg_dict.set_item("start", pos)?;
+            seg_dict.set_item("end", pos + seg.text.len())?;
+            pos += seg.text.len();
+
+            let anns = PyList::empty_bound(py);
+            for id in &seg.annotations {
+                anns.append(annotation_to_dict(py, self.inner.annotation(*id))?)?;
+            }
+            seg_dict.set_item("annotations", anns)?;
+            segments.append(seg_dict)?;
+        }
+        dict.set_item("segments", segments)?;
+
+        let markers = PyList::empty_bound(py);
+        for marker in &self.inner.markers {
+            let marker_dict = PyDict::new_bound(py);
+            marker_dict.set_item("pos", marker.pos)?;
+            marker_dict.set_item(
+                "annotation",
+                annotation_to_dict(py, self.inner.annotation(marker.annotation))?,
+            )?;
+            markers.append(marker_dict)?;
+        }
+        dict.set_item("markers", markers)?;
+
+        Ok(dict.into_py(py))
+    }
+
+    /// `to_dict()`, serialized with the stdlib `json` module.
+    fn to_json(&self, py: Python<'_>) -> PyResult<String> {
+        let dict = self.to_dict(py)?;
+        let json_mod = PyModule::import_bound(py, "json")?;
+        json_mod.call_method1("dumps", (dict,))?.extract()
+    }
+
+    /// Reconstructs a `ParseResult` from a dict shaped like `to_dict()`'s
+    /// output, without re-parsing. Segments/markers built this way carry no
+    /// input-position tracking (`span` is always `None`).
+    #[classmethod]
+    fn from_dict(_cls: &Bound<'_, PyType>, data: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let text: String = data
+            .get_item("text")?
+            .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err("missing 'text'"))?
+            .extract()?;
+
+        let mut annotations = Vec::new();
+        let mut segments = Vec::new();
+        if let Some(seg_list) = data.get_item("segments")? {
+            for seg_any in seg_list.downcast::<PyList>()?.iter() {
+                let seg_dict: &Bound<PyDict> = seg_any.downcast()?;
+                let seg_text: String = seg_dict
+                    .get_item("text")?
+                    .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err("segment missing 'text'"))?
+                    .extract()?;
+                let mut ids = Vec::new();
+                if let Some(ann_list) = seg_dict.get_item("annotations")? {
+                    for ann_any in ann_list.downcast::<PyList>()?.iter() {
+                        let ann = annotation_from_dict(ann_any.downcast()?)?;
+                        ids.push(AnnotationId::new(annotations.len()));
+                        annotations.push(ann);
+                    }
+                }
+                segments.push(Segment {
+                    text: seg_text,
+                    annotations: ids,
+                    span: None,
+                });
+            }
+        }
+
+        let mut markers = Vec::new();
+        if let Some(marker_list) = data.get_item("markers")? {
+            for marker_any in marker_list.downcast::<PyList>()?.iter() {
+                let marker_dict: &Bound<PyDict> = marker_any.downcast()?;
+                let pos: usize = marker_dict
+                    .get_item("pos")?
+                    .ok_or_else(|| pyo3::exceptions::PyKeyError::new_err("marker missing 'pos'"))?
+                    .extract()?;
+                let ann_any = marker_dict.get_item("annotation")?.ok_or_else(|| {
+                    pyo3::exceptions::PyKeyError::new_err("marker missing 'annotation'")
+                })?;
+                let ann_dict: &Bound<PyDict> = ann_any.downcast()?;
+                let ann = annotation_from_dict(ann_dict)?;
+                let id = AnnotationId::new(annotations.len());
+                annotations.push(ann);
+                markers.push(Marker {
+                    pos,
+                    annotation: id,
+                    span: None,
+                });
+            }
+        }
+
+        Ok(Self {
+            inner: ParseResult {
+                text,
+                segments,
+                markers,
+                diagnostics: Vec::new(),
+                attr_diagnostics: None,
+                annotations,
+                source_spans: Vec::new(),
+            },
+            references: Vec::new(),
+        })
+    }
+
+    /// Re-emits canonical, well-formed KindaXML markup (normalized attrs,
+    /// nested open/close tags, self-closing markers) from `segments`/
+    /// `markers` — a pretty-printer for lenient input, suitable for
+    /// re-feeding or diffing.
+    fn to_kindaxml(&self) -> String {
+        self.inner.to_markup(&ParserConfig::default())
+    }
+
     fn __repr__(&self) -> PyResult<String> {
         Ok(format!(
-            "ParseResult(text_len={}, segments={}, markers={})",
+            "ParseResult(text_len={}, segments={}, markers={}, diagnostics={}, references={})",
             self.inner.text.len(),
             self.inner.segments.len(),
-            self.inner.markers.len()
+            self.inner.markers.len(),
+            self.inner.diagnostics.len(),
+            self.references.len()
         ))
     }
 }
 
 #[pyclass(name = "ParserConfig")]
+#[derive(Clone)]
 pub struct PyParserConfig {
     inner: ParserConfig,
+    /// Not a `ParserConfig` field: this is a post-processing step over the
+    /// finished `ParseResult`, not something the parse loop itself needs.
+    resolve_references: bool,
 }
 
 #[pymethods]
@@ -179,6 +655,7 @@ impl PyParserConfig {
     pub fn new() -> Self {
         Self {
             inner: ParserConfig::default(),
+            resolve_references: false,
         }
     }
 
@@ -186,6 +663,7 @@ impl PyParserConfig {
     pub fn default_llm_friendly_config(_cls: &Bound<'_, PyType>) -> Self {
         Self {
             inner: ParserConfig::default_llm_friendly_config(),
+            resolve_references: false,
         }
     }
 
@@ -193,6 +671,7 @@ impl PyParserConfig {
     pub fn default_cite_config(_cls: &Bound<'_, PyType>) -> Self {
         Self {
             inner: ParserConfig::default_cite_config(),
+            resolve_references: false,
         }
     }
 
@@ -282,6 +761,71 @@ impl PyParserConfig {
         slf
     }
 
+    /// Toggle confusable/homoglyph folding of tag delimiters and names
+    /// (fullwidth brackets, Cyrillic/Greek look-alike letters, ...).
+    pub fn with_normalize_confusables<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        val: bool,
+    ) -> PyRefMut<'a, Self> {
+        slf.inner.normalize_confusables = val;
+        slf
+    }
+
+    /// Set the canonical word-casing scheme tag names are normalized to
+    /// before alias/`recognized_tags` matching: "snake", "kebab", "camel",
+    /// "pascal", or "screaming_snake".
+    pub fn with_tag_case_style<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        style: &str,
+    ) -> PyResult<PyRefMut<'a, Self>> {
+        slf.inner.tag_case_style = Some(match style.to_ascii_lowercase().as_str() {
+            "snake" => TagCaseStyle::Snake,
+            "kebab" => TagCaseStyle::Kebab,
+            "camel" => TagCaseStyle::Camel,
+            "pascal" => TagCaseStyle::Pascal,
+            "screaming_snake" => TagCaseStyle::ScreamingSnake,
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "unknown tag case style '{}'",
+                    other
+                )));
+            }
+        });
+        Ok(slf)
+    }
+
+    /// Map arbitrary source tag spellings onto a canonical recognized tag.
+    pub fn with_tag_aliases<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        aliases: HashMap<String, String>,
+    ) -> PyRefMut<'a, Self> {
+        slf.inner.tag_aliases = aliases;
+        slf
+    }
+
+    /// Toggle collecting `ParseResult.attr_diagnostics`: unterminated
+    /// quotes, dangling `=`, duplicate attribute names, and raw `<` inside
+    /// unquoted values. Off by default, same as `track_positions`.
+    pub fn with_collect_attr_diagnostics<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        val: bool,
+    ) -> PyRefMut<'a, Self> {
+        slf.inner.collect_attr_diagnostics = val;
+        slf
+    }
+
+    /// Toggle the citation/reference resolution pass: when on, `ParseResult`
+    /// (via `parse`/`StreamingParser.finish`) also collects every citation
+    /// annotation (tag `cite`, keyed by its `id` attr, or `ref` if no
+    /// annotation has an `id`) into a deduplicated `references` list.
+    pub fn with_resolve_references<'a>(
+        mut slf: PyRefMut<'a, Self>,
+        val: bool,
+    ) -> PyRefMut<'a, Self> {
+        slf.resolve_references = val;
+        slf
+    }
+
     fn __repr__(&self) -> String {
         // format recognized tags as a sorted list
         let mut tags: Vec<_> = self.inner.recognized_tags.iter().cloned().collect();
@@ -341,6 +885,79 @@ impl PyParserConfig {
     }
 }
 
+/// Resumable parser for LLM output that arrives token-by-token. Wraps the
+/// core [`IncrementalParser`]: `feed` appends a chunk and returns whatever
+/// `Segment`/`Marker` values just finalized (an incomplete trailing tag and
+/// text inside a still-open tag stay buffered; text before an open tag does
+/// not, so a later unclosed tag recovered with `RetroLine` can annotate
+/// text an earlier `feed` already returned without it); `finish` applies
+/// the configured recovery strategies to whatever is still open and returns
+/// the final `ParseResult`, exactly like calling `parse` on the whole buffer.
+///
+/// `finish` consumes the parser, so it's only callable once; `inner` is an
+/// `Option` purely to let `finish(&mut self)` move the `IncrementalParser`
+/// out from behind the `Py<Self>` cell that every `#[pymethods]` receiver
+/// is bound through.
+#[pyclass(name = "StreamingParser")]
+pub struct PyStreamingParser {
+    inner: Option<IncrementalParser>,
+    resolve_references: bool,
+}
+
+#[pymethods]
+impl PyStreamingParser {
+    #[classattr]
+    const __doc__: &'static str =
+        "StreamingParser(config: ParserConfig) -> incremental parser for streamed LLM output.";
+
+    #[new]
+    pub fn new(config: &PyParserConfig) -> Self {
+        Self {
+            inner: Some(IncrementalParser::new(config.inner.clone())),
+            resolve_references: config.resolve_references,
+        }
+    }
+
+    fn feed<'py>(
+        &mut self,
+        py: Python<'py>,
+        chunk: &str,
+    ) -> PyResult<(Vec<Py<PySegment>>, Vec<Py<PyMarker>>)> {
+        let parser = self.inner.as_mut().ok_or_else(|| {
+            pyo3::exceptions::PyRuntimeError::new_err("feed() called after finish()")
+        })?;
+        let output = parser.feed(chunk);
+
+        let segments = output
+            .segments
+            .iter()
+            .map(|s| PySegment::from_core(s, |id| parser.annotation(id)))
+            .map(|s| Py::new(py, s))
+            .collect::<PyResult<Vec<_>>>()?;
+        let markers = output
+            .markers
+            .iter()
+            .map(|m| PyMarker::from_core(m, |id| parser.annotation(id)))
+            .map(|m| Py::new(py, m))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok((segments, markers))
+    }
+
+    fn finish(&mut self) -> PyResult<PyParseResult> {
+        let parser = self
+            .inner
+            .take()
+            .ok_or_else(|| pyo3::exceptions::PyRuntimeError::new_err("finish() already called"))?;
+        let inner = parser.finish();
+        let references = if self.resolve_references {
+            resolve_citation_references(&inner)
+        } else {
+            Vec::new()
+        };
+        Ok(PyParseResult { inner, references })
+    }
+}
+
 #[pyfunction(name = "parse")]
 #[pyo3(text_signature = "(text, config=None)")]
 /// Parse KindaXML text using the default config (case-insensitive tags, cite retro, others forward).
@@ -352,8 +969,21 @@ pub fn py_parse(
     let cfg = config
         .map(|c| c.inner.clone())
         .unwrap_or_else(ParserConfig::default_llm_friendly_config);
+    let resolve_refs = config.is_some_and(|c| c.resolve_references);
     let result = parse(input, &cfg);
-    Py::new(py, PyParseResult { inner: result }).map(|obj| obj.into_py(py))
+    let references = if resolve_refs {
+        resolve_citation_references(&result)
+    } else {
+        Vec::new()
+    };
+    Py::new(
+        py,
+        PyParseResult {
+            inner: result,
+            references,
+        },
+    )
+    .map(|obj| obj.into_py(py))
 }
 
 #[pymodule]
@@ -363,7 +993,11 @@ pub fn python_module(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PySegment>()?;
     m.add_class::<PyAnnotation>()?;
     m.add_class::<PyMarker>()?;
+    m.add_class::<PyDiagnostic>()?;
+    m.add_class::<PyAttrDiagnostic>()?;
+    m.add_class::<PyReference>()?;
     m.add_class::<PyParserConfig>()?;
+    m.add_class::<PyStreamingParser>()?;
     m.add_function(wrap_pyfunction!(py_parse, m)?)?;
     Ok(())
 }